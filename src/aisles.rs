@@ -0,0 +1,410 @@
+use std::collections::BTreeMap;
+
+use egui::Grid;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    typography::icons::{ICON_ARROW_DOWN, ICON_ARROW_UP, ICON_PLUS, ICON_TRASH_2},
+    util::hs,
+};
+
+pub const PRODUCE: &str = "Produce";
+pub const BAKERY: &str = "Bakery";
+pub const MEAT_FISH: &str = "Meat & fish";
+pub const DAIRY_EGGS: &str = "Dairy & eggs";
+pub const PANTRY: &str = "Pantry";
+pub const SPICES: &str = "Spices & condiments";
+pub const FROZEN: &str = "Frozen";
+pub const DRINKS: &str = "Drinks";
+pub const OTHER: &str = "Other";
+
+const DEFAULT_LAYOUT: [&str; 9] = [
+    PRODUCE, BAKERY, MEAT_FISH, DAIRY_EGGS, PANTRY, SPICES, FROZEN, DRINKS, OTHER,
+];
+
+/// Edamam food categories by keyword, checked in order.
+const CATEGORY_AISLES: [(&str, &str); 19] = [
+    ("frozen", FROZEN),
+    ("canned", PANTRY),
+    ("vegetable", PRODUCE),
+    ("fruit", PRODUCE),
+    ("bread", BAKERY),
+    ("egg", DAIRY_EGGS),
+    ("dairy", DAIRY_EGGS),
+    ("cheese", DAIRY_EGGS),
+    ("milk", DAIRY_EGGS),
+    ("poultry", MEAT_FISH),
+    ("meat", MEAT_FISH),
+    ("fish", MEAT_FISH),
+    ("seafood", MEAT_FISH),
+    ("condiment", SPICES),
+    ("spice", SPICES),
+    ("oil", PANTRY),
+    ("grain", PANTRY),
+    ("beverage", DRINKS),
+    ("water", DRINKS),
+];
+
+/// Food names by keyword, for ingredients analysed without a category. Checked in
+/// order, so the more specific keywords come first.
+const FOOD_AISLES: [(&str, &str); 77] = [
+    ("frozen", FROZEN),
+    ("canned", PANTRY),
+    ("stock", PANTRY),
+    ("broth", PANTRY),
+    ("lemon juice", PRODUCE),
+    ("juice", PANTRY),
+    ("paste", PANTRY),
+    ("protein powder", PANTRY),
+    ("powder", SPICES),
+    ("bell pepper", PRODUCE),
+    ("peppercorn", SPICES),
+    ("pepperoncini", PANTRY),
+    ("pepper", SPICES),
+    ("salt", SPICES),
+    ("sauce", SPICES),
+    ("vinegar", SPICES),
+    ("mirin", SPICES),
+    ("cinnamon", SPICES),
+    ("cumin", SPICES),
+    ("paprika", SPICES),
+    ("turmeric", SPICES),
+    ("five spice", SPICES),
+    ("dried", SPICES),
+    ("baking soda", PANTRY),
+    ("oil", PANTRY),
+    ("rice", PANTRY),
+    ("pasta", PANTRY),
+    ("quinoa", PANTRY),
+    ("flour", PANTRY),
+    ("sugar", PANTRY),
+    ("granola", PANTRY),
+    ("green bean", PRODUCE),
+    ("bean", PANTRY),
+    ("lentil", PANTRY),
+    ("chickpea", PANTRY),
+    ("cashew", PANTRY),
+    ("peanut", PANTRY),
+    ("nutmeg", SPICES),
+    ("nut", PANTRY),
+    ("breadcrumb", PANTRY),
+    ("tortilla", BAKERY),
+    ("bread", BAKERY),
+    ("bun", BAKERY),
+    ("egg", DAIRY_EGGS),
+    ("cheese", DAIRY_EGGS),
+    ("yogurt", DAIRY_EGGS),
+    ("buttermilk", DAIRY_EGGS),
+    ("milk", DAIRY_EGGS),
+    ("butter", DAIRY_EGGS),
+    ("cream", DAIRY_EGGS),
+    ("chicken", MEAT_FISH),
+    ("turkey", MEAT_FISH),
+    ("beef", MEAT_FISH),
+    ("steak", MEAT_FISH),
+    ("veal", MEAT_FISH),
+    ("lamb", MEAT_FISH),
+    ("pork", MEAT_FISH),
+    ("ham", MEAT_FISH),
+    ("bacon", MEAT_FISH),
+    ("salmon", MEAT_FISH),
+    ("tuna", MEAT_FISH),
+    ("cod", MEAT_FISH),
+    ("shrimp", MEAT_FISH),
+    ("squid", MEAT_FISH),
+    ("clam", MEAT_FISH),
+    ("fish", MEAT_FISH),
+    ("wine", DRINKS),
+    ("beer", DRINKS),
+    ("ale", DRINKS),
+    ("water", DRINKS),
+    ("coffee", DRINKS),
+    ("tea", DRINKS),
+    ("tomato", PRODUCE),
+    ("potato", PRODUCE),
+    ("onion", PRODUCE),
+    ("garlic", PRODUCE),
+    ("lettuce", PRODUCE),
+];
+
+/// Fresh herbs, fruit and vegetables that don't match anything above.
+const PRODUCE_WORDS: [&str; 34] = [
+    "avocado",
+    "banana",
+    "berry",
+    "blueberry",
+    "raspberry",
+    "strawberry",
+    "cabbage",
+    "carrot",
+    "chili",
+    "chives",
+    "cilantro",
+    "coriander",
+    "cucumber",
+    "dill",
+    "ginger",
+    "kale",
+    "lemon",
+    "lime",
+    "mint",
+    "orange",
+    "parsley",
+    "pea",
+    "pear",
+    "shallot",
+    "spinach",
+    "apple",
+    "basil",
+    "mushroom",
+    "zucchini",
+    "broccoli",
+    "eggplant",
+    "melon",
+    "watermelon",
+    "squash",
+];
+
+/// Whether `keyword` is a word of `text`, alone or as a plural, so "tea" matches
+/// "green tea" but not "steak" and "egg" matches "eggs" but not "eggplant".
+fn has_word(text: &str, keyword: &str) -> bool {
+    let ies_plural = keyword
+        .strip_suffix('y')
+        .is_some_and(|stem| has_word_ending(text, &format!("{}ies", stem), &[""]));
+    ies_plural || has_word_ending(text, keyword, &["", "s", "es"])
+}
+
+/// Whether `word` followed by one of `endings` is a word of `text`.
+fn has_word_ending(text: &str, word: &str, endings: &[&str]) -> bool {
+    let starts_with_letter = |text: &str| text.chars().next().is_some_and(char::is_alphabetic);
+    text.match_indices(word).any(|(idx, _)| {
+        let after = &text[idx + word.len()..];
+        let word_starts = !text[..idx]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphabetic);
+        let word_ends = endings.iter().any(|plural| {
+            after
+                .strip_prefix(plural)
+                .is_some_and(|rest| !starts_with_letter(rest))
+        });
+        word_starts && word_ends
+    })
+}
+
+/// The aisle a food is usually found in, from its Edamam category when the
+/// analysis has one, otherwise from its name.
+pub fn default_aisle(food: &str, category: Option<&str>) -> &'static str {
+    let food = food.to_lowercase();
+    let by_category = category.and_then(|category| {
+        let category = category.to_lowercase();
+        CATEGORY_AISLES
+            .iter()
+            .find(|(keyword, _)| has_word(&category, keyword))
+    });
+    by_category
+        .or_else(|| {
+            FOOD_AISLES
+                .iter()
+                .find(|(keyword, _)| has_word(&food, keyword))
+        })
+        .map(|(_, aisle)| *aisle)
+        .or_else(|| {
+            PRODUCE_WORDS
+                .iter()
+                .any(|word| has_word(&food, word))
+                .then_some(PRODUCE)
+        })
+        .unwrap_or(OTHER)
+}
+
+/// The aisles of the store in walking order, and the aisle the user moved foods to.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StoreLayout {
+    pub aisles: Vec<String>,
+    /// Aisle by lowercase food name, overriding the default one.
+    food_aisles: BTreeMap<String, String>,
+    #[serde(skip)]
+    new_aisle: String,
+}
+
+impl Default for StoreLayout {
+    fn default() -> Self {
+        Self {
+            aisles: DEFAULT_LAYOUT
+                .iter()
+                .map(|aisle| aisle.to_string())
+                .collect(),
+            food_aisles: BTreeMap::new(),
+            new_aisle: String::new(),
+        }
+    }
+}
+
+impl StoreLayout {
+    pub fn aisle_for(&self, food: &str, category: Option<&str>) -> String {
+        self.food_aisles
+            .get(&food.to_lowercase())
+            .cloned()
+            .unwrap_or_else(|| default_aisle(food, category).to_string())
+    }
+
+    /// Moves `food` to `aisle`, or back to its default aisle with `None`.
+    pub fn set_aisle(&mut self, food: &str, aisle: Option<String>) {
+        match aisle {
+            Some(aisle) => self.food_aisles.insert(food.to_lowercase(), aisle),
+            None => self.food_aisles.remove(&food.to_lowercase()),
+        };
+    }
+
+    pub fn is_moved(&self, food: &str) -> bool {
+        self.food_aisles.contains_key(&food.to_lowercase())
+    }
+
+    /// Position of an aisle in the store. Aisles missing from the layout go last.
+    pub fn position(&self, aisle: &str) -> usize {
+        self.aisles
+            .iter()
+            .position(|a| a == aisle)
+            .unwrap_or(self.aisles.len())
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(hs(
+            "Shopping list groups follow this order. Foods in an aisle that is not listed go last.",
+        ));
+        let mut move_up = None;
+        let mut remove = None;
+        Grid::new("store_layout")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                let count = self.aisles.len();
+                for (idx, aisle) in self.aisles.iter().enumerate() {
+                    ui.label(aisle.as_str());
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(idx > 0, egui::Button::new(ICON_ARROW_UP))
+                            .on_hover_text("Earlier in the store")
+                            .clicked()
+                        {
+                            move_up = Some(idx);
+                        }
+                        if ui
+                            .add_enabled(idx + 1 < count, egui::Button::new(ICON_ARROW_DOWN))
+                            .on_hover_text("Later in the store")
+                            .clicked()
+                        {
+                            move_up = Some(idx + 1);
+                        }
+                    });
+                    if ui
+                        .button(ICON_TRASH_2)
+                        .on_hover_text("Remove aisle")
+                        .clicked()
+                    {
+                        remove = Some(idx);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(idx) = move_up {
+            self.aisles.swap(idx - 1, idx);
+        }
+        if let Some(idx) = remove {
+            self.aisles.remove(idx);
+        }
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.new_aisle);
+            let name = self.new_aisle.trim().to_string();
+            let valid = !name.is_empty() && !self.aisles.contains(&name);
+            if ui
+                .add_enabled(valid, egui::Button::new(format!("{} Add aisle", ICON_PLUS)))
+                .clicked()
+            {
+                self.aisles.push(name);
+                self.new_aisle.clear();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn has_word_matches_whole_words() {
+        assert!(has_word("green tea", "tea"));
+        assert!(!has_word("steak", "tea"));
+        assert!(!has_word("eggplant", "egg"));
+        assert!(!has_word("butternut squash", "butter"));
+        assert!(!has_word("watermelon", "water"));
+        assert!(has_word("water, sparkling", "water"));
+        assert!(has_word("butter", "butter"));
+    }
+
+    #[test]
+    fn has_word_matches_plurals() {
+        assert!(has_word("large eggs", "egg"));
+        assert!(has_word("cherry tomatoes", "tomato"));
+        assert!(has_word("beans, drained", "bean"));
+        assert!(!has_word("eggsplant", "egg"));
+        assert!(has_word("mixed berries", "berry"));
+        assert!(!has_word("berrying", "berry"));
+    }
+
+    #[test]
+    fn produce_words_match_whole_words() {
+        assert_eq!(default_aisle("peanut butter", None), PANTRY);
+        assert_eq!(default_aisle("pear", None), PRODUCE);
+        assert_eq!(default_aisle("frozen peas", None), FROZEN);
+        assert_eq!(default_aisle("snow peas", None), PRODUCE);
+        assert_eq!(default_aisle("sublime sauce", None), SPICES);
+        assert_eq!(default_aisle("sublime", None), OTHER);
+        assert_eq!(default_aisle("ginger ale", None), DRINKS);
+        assert_eq!(default_aisle("fresh ginger", None), PRODUCE);
+        assert_eq!(default_aisle("blueberries", None), PRODUCE);
+        assert_eq!(default_aisle("mixed berries", None), PRODUCE);
+    }
+
+    #[test]
+    fn default_aisle_by_name() {
+        assert_eq!(default_aisle("Eggs", None), DAIRY_EGGS);
+        assert_eq!(default_aisle("eggplant", None), PRODUCE);
+        assert_eq!(default_aisle("unsalted butter", None), DAIRY_EGGS);
+        assert_eq!(default_aisle("butternut squash", None), PRODUCE);
+        assert_eq!(default_aisle("sparkling water", None), DRINKS);
+        assert_eq!(default_aisle("watermelon", None), PRODUCE);
+        assert_eq!(default_aisle("black peppercorns", None), SPICES);
+        assert_eq!(default_aisle("red bell pepper", None), PRODUCE);
+        assert_eq!(default_aisle("gizmo", None), OTHER);
+    }
+
+    #[test]
+    fn moved_foods_override_the_default_aisle() {
+        let mut layout = StoreLayout::default();
+        layout.set_aisle("Tofu", Some(DAIRY_EGGS.to_string()));
+        assert_eq!(layout.aisle_for("tofu", None), DAIRY_EGGS);
+        assert!(layout.is_moved("TOFU"));
+        layout.set_aisle("tofu", None);
+        assert_eq!(layout.aisle_for("tofu", None), OTHER);
+        assert!(layout.position(PRODUCE) < layout.position(FROZEN));
+        assert_eq!(layout.position("Garden"), layout.aisles.len());
+    }
+
+    #[test]
+    fn default_aisle_prefers_the_category() {
+        assert_eq!(default_aisle("peas", Some("frozen vegetables")), FROZEN);
+        assert_eq!(default_aisle("peas", Some("unknown")), PRODUCE);
+        assert_eq!(default_aisle("tofu", Some("Plant-based protein")), OTHER);
+        assert_eq!(default_aisle("feta", Some("Cheese")), DAIRY_EGGS);
+        assert_eq!(
+            default_aisle("salsa", Some("Condiments and sauces")),
+            SPICES
+        );
+        assert_eq!(default_aisle("lager", Some("Beverages")), DRINKS);
+    }
+}
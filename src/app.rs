@@ -5,17 +5,29 @@ use std::{
 };
 
 use base64::prelude::*;
+use egui::{Button, DragValue};
+use egui_extras::DatePickerButton;
 use rfd::FileHandle;
 use uuid::Uuid;
 
 use crate::{
-    meal_planner::MealPlanner,
+    calendar::CalendarExport,
+    diary::FoodDiary,
+    food_names::FoodNamesView,
+    meal_planner::{MealPlanner, MAX_PLAN_DAYS, MIN_PLAN_DAYS},
     models::AnalysisResponse,
+    pantry::PantryView,
+    plan_generator::PlanGenerator,
     planner::Planner,
+    prices::PricesView,
+    printout::plan_pdf,
     recipe_editor::Editor,
     recipe_gallery::RecipeGallery,
     shopping_list::ShoppingList,
-    util::{percentage, DEFAULT_PADDING},
+    summary::PlanSummary,
+    templates::TemplateLibrary,
+    typography::icons::ICON_TRASH_2,
+    util::{hs, percentage, save_file, DEFAULT_PADDING},
 };
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -33,7 +45,7 @@ pub enum Download {
     #[default]
     None,
     InProgress,
-    Done(ehttp::Result<AnalysisResponse>),
+    Done(ehttp::Result<Box<AnalysisResponse>>),
 }
 
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
@@ -51,13 +63,40 @@ pub struct MealPlannerApp {
     #[serde(skip)]
     pub settings_window_visible: bool,
     #[serde(skip)]
+    pub plan_generator_visible: bool,
+    #[serde(skip)]
+    pub summary_visible: bool,
+    #[serde(skip)]
+    summary: PlanSummary,
+    #[serde(skip)]
+    pub diary_visible: bool,
+    #[serde(skip)]
+    diary: FoodDiary,
+    #[serde(skip)]
+    pub pantry_visible: bool,
+    #[serde(skip)]
+    pantry: PantryView,
+    #[serde(skip)]
+    pub prices_visible: bool,
+    #[serde(skip)]
+    prices: PricesView,
+    #[serde(skip)]
+    food_names: FoodNamesView,
+    #[serde(skip)]
+    pub templates_visible: bool,
+    #[serde(skip)]
+    templates: TemplateLibrary,
+    #[serde(skip)]
     download: Arc<Mutex<Download>>,
     #[serde(skip)]
     import_data: Arc<Mutex<(String, Vec<u8>)>>,
     #[serde(skip)]
     recipe_gallery: RecipeGallery,
-    #[serde(skip)]
     shopping_list: ShoppingList,
+    plan_generator: PlanGenerator,
+    #[serde(skip)]
+    pub calendar_visible: bool,
+    calendar: CalendarExport,
     meal_planner: MealPlanner,
 }
 
@@ -69,7 +108,22 @@ impl Default for MealPlannerApp {
             editor_recipe_id: None,
             shopping_list_visible: false,
             settings_window_visible: false,
+            plan_generator_visible: false,
+            summary_visible: false,
+            summary: PlanSummary::default(),
+            diary_visible: false,
+            diary: FoodDiary::default(),
+            pantry_visible: false,
+            pantry: PantryView::default(),
+            prices_visible: false,
+            prices: PricesView::default(),
+            food_names: FoodNamesView::default(),
+            templates_visible: false,
+            templates: TemplateLibrary::default(),
             shopping_list: ShoppingList::default(),
+            plan_generator: PlanGenerator::default(),
+            calendar_visible: false,
+            calendar: CalendarExport::default(),
             download: Arc::new(Mutex::new(Download::None)),
             import_data: Arc::new(Mutex::new((String::new(), vec![]))),
             meal_planner: MealPlanner::default(),
@@ -88,7 +142,10 @@ impl MealPlannerApp {
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
         if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+            let mut state: MealPlannerApp =
+                eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+            state.meal_planner.normalize_plan();
+            return state;
         }
 
         Default::default()
@@ -102,12 +159,14 @@ impl MealPlannerApp {
         let json = BASE64_STANDARD
             .decode(include_bytes!("../state.json"))
             .unwrap();
-        let default_state = serde_json::from_slice(json.as_slice()).unwrap();
+        let mut default_state: MealPlannerApp = serde_json::from_slice(json.as_slice()).unwrap();
+        default_state.meal_planner.normalize_plan();
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
         if let Some(storage) = cc.storage {
-            let previous_state: MealPlannerApp =
+            let mut previous_state: MealPlannerApp =
                 eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+            previous_state.meal_planner.normalize_plan();
             if previous_state.meal_planner.is_daily_plan_empty()
                 && previous_state.meal_planner.get_recipes().len() == 0
             {
@@ -192,7 +251,7 @@ impl eframe::App for MealPlannerApp {
                     let decoded = BASE64_STANDARD.decode(content).unwrap();
                     if self
                         .meal_planner
-                        .from_json(std::str::from_utf8(decoded.as_slice()).unwrap())
+                        .import_json(std::str::from_utf8(decoded.as_slice()).unwrap())
                     {
                         println!("Successful");
                     }
@@ -203,7 +262,7 @@ impl eframe::App for MealPlannerApp {
                 if !lock.1.is_empty() {
                     let decoded = BASE64_STANDARD.decode(&lock.1).unwrap();
                     self.meal_planner
-                        .from_json(std::str::from_utf8(decoded.as_slice()).unwrap());
+                        .import_json(std::str::from_utf8(decoded.as_slice()).unwrap());
                     lock.1 = vec![];
                 }
             }
@@ -243,6 +302,42 @@ impl eframe::App for MealPlannerApp {
                     self.shopping_list_visible = true;
                 }
 
+                if ui.button("Pantry").clicked() {
+                    self.pantry_visible = true;
+                }
+
+                if ui.button("Prices").clicked() {
+                    self.prices_visible = true;
+                }
+
+                if ui.button("Generate Plan").clicked() {
+                    self.plan_generator_visible = true;
+                }
+
+                if ui.button("Templates").clicked() {
+                    self.templates_visible = true;
+                }
+
+                if ui.button("Summary").clicked() {
+                    self.summary_visible = true;
+                }
+
+                if ui.button("Diary").clicked() {
+                    self.diary_visible = true;
+                }
+
+                if ui.button("Calendar").clicked() {
+                    self.calendar_visible = true;
+                }
+
+                if ui
+                    .button("Print")
+                    .on_hover_text("Save the plan and its recipes as a PDF")
+                    .clicked()
+                {
+                    save_file("meal-plan.pdf", plan_pdf(&self.meal_planner));
+                }
+
                 if ui.button("Settings").clicked() {
                     self.settings_window_visible = true;
                 }
@@ -254,7 +349,7 @@ impl eframe::App for MealPlannerApp {
 
         // Resizable bottom panel for the planner
         let screen_height = ctx.screen_rect().height();
-        egui::TopBottomPanel::bottom("planner_panel")
+        let planner_edit_recipe = egui::TopBottomPanel::bottom("planner_panel")
             .resizable(true)
             .show_separator_line(false)
             .min_height(screen_height * 0.15)
@@ -271,10 +366,15 @@ impl eframe::App for MealPlannerApp {
                         blur: 8.0,
                         spread: 2.0,
                     })
-                    .show(ui, |ui| {
-                        self.planner.ui(ui, &mut self.meal_planner);
-                    });
-            });
+                    .show(ui, |ui| self.planner.ui(ui, &mut self.meal_planner))
+                    .inner
+            })
+            .inner;
+
+        if planner_edit_recipe.is_some() {
+            self.editor_recipe_id = planner_edit_recipe;
+            self.editor_visible = true;
+        }
 
         // Central panel for recipe browser
         let edit_recipe = egui::CentralPanel::default()
@@ -295,7 +395,9 @@ impl eframe::App for MealPlannerApp {
             .default_width(percentage(ctx.screen_rect().width(), 80))
             .show(&ctx.clone(), |ui| {
                 if let Some(id) = self.editor_recipe_id {
-                    Editor::new().ui(ui, self.meal_planner.get_recipe_by_id_mut(&id).unwrap())
+                    self.meal_planner
+                        .edit_recipe(&id, |recipe| Editor::new().ui(ui, recipe))
+                        .flatten()
                 } else {
                     None
                 }
@@ -326,7 +428,71 @@ impl eframe::App for MealPlannerApp {
             .min_height(300.)
             .resizable(true)
             .show(&ctx.clone(), |ui| {
-                self.shopping_list.show(ui, &self.meal_planner);
+                self.shopping_list.show(ui, &mut self.meal_planner);
+            });
+
+        // Pantry window
+        egui::Window::new("Pantry")
+            .open(&mut self.pantry_visible)
+            .resizable(true)
+            .show(&ctx.clone(), |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.pantry.ui(ui, &mut self.meal_planner);
+                });
+            });
+
+        egui::Window::new("Prices")
+            .open(&mut self.prices_visible)
+            .resizable(true)
+            .show(&ctx.clone(), |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.prices.ui(ui, &mut self.meal_planner);
+                });
+            });
+
+        // Plan generator window
+        egui::Window::new("Generate Plan")
+            .open(&mut self.plan_generator_visible)
+            .min_height(300.)
+            .resizable(true)
+            .show(&ctx.clone(), |ui| {
+                self.plan_generator.ui(ui, &mut self.meal_planner);
+            });
+
+        // Plan templates window
+        egui::Window::new("Plan Templates")
+            .open(&mut self.templates_visible)
+            .resizable(true)
+            .show(&ctx.clone(), |ui| {
+                self.templates.ui(ui, &mut self.meal_planner);
+            });
+
+        // Plan summary window
+        egui::Window::new("Plan Summary")
+            .open(&mut self.summary_visible)
+            .min_height(300.)
+            .resizable(true)
+            .show(&ctx.clone(), |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.summary.ui(ui, &self.meal_planner);
+                });
+            });
+
+        // Food diary window
+        egui::Window::new("Food Diary")
+            .open(&mut self.diary_visible)
+            .min_height(300.)
+            .resizable(true)
+            .show(&ctx.clone(), |ui| {
+                self.diary.ui(ui, &mut self.meal_planner);
+            });
+
+        // Calendar export window
+        egui::Window::new("Export to Calendar")
+            .open(&mut self.calendar_visible)
+            .resizable(false)
+            .show(&ctx.clone(), |ui| {
+                self.calendar.ui(ui, &self.meal_planner);
             });
 
         // Settings window
@@ -346,6 +512,118 @@ impl eframe::App for MealPlannerApp {
                         ui.text_edit_singleline(&mut self.meal_planner.app_id);
                     });
                 });
+
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Days to plan");
+                        let mut plan_length = self.meal_planner.plan_length();
+                        let drag = DragValue::new(&mut plan_length)
+                            .range(MIN_PLAN_DAYS..=MAX_PLAN_DAYS)
+                            .suffix(" days");
+                        if ui
+                            .add(drag)
+                            .on_hover_text("Shortening the plan removes the meals of the last days")
+                            .changed()
+                        {
+                            self.meal_planner.set_plan_length(plan_length);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Plan starts on");
+                        let mut plan_start = self.meal_planner.get_plan_start();
+                        let date_picker = DatePickerButton::new(&mut plan_start)
+                            .id_salt("settings_plan_start");
+                        if ui.add(date_picker).changed() {
+                            self.meal_planner.set_plan_start(plan_start);
+                        }
+                    });
+                });
+
+                ui.group(|ui| {
+                    ui.label("Meal slots");
+                    let mut remove_slot = None;
+                    let slot_count = self.meal_planner.get_meal_slots().len();
+                    for slot_idx in 0..slot_count {
+                        let mut name = self.meal_planner.get_meal_slots()[slot_idx].clone();
+                        ui.horizontal(|ui| {
+                            if ui.text_edit_singleline(&mut name).changed() {
+                                self.meal_planner.rename_meal_slot(slot_idx, &name);
+                            }
+                            let remove_btn = Button::new(ICON_TRASH_2);
+                            if ui
+                                .add_enabled(slot_count > 1, remove_btn)
+                                .on_hover_text("Remove slot, its meals move to the previous one")
+                                .clicked()
+                            {
+                                remove_slot = Some(slot_idx);
+                            }
+                        });
+                    }
+                    if let Some(slot_idx) = remove_slot {
+                        self.meal_planner.remove_meal_slot(slot_idx);
+                    }
+                    if ui.button("Add slot").clicked() {
+                        self.meal_planner.add_meal_slot("New slot");
+                    }
+                });
+
+                ui.group(|ui| {
+                    ui.label("Ingredient names");
+                    self.food_names.ui(ui, &mut self.meal_planner);
+                });
+
+                ui.group(|ui| {
+                    ui.label("Household");
+                    ui.label(hs(
+                        "Without members the plan is for one person. Members get the daily targets of the plan generator to start with.",
+                    ));
+                    let mut remove_member = None;
+                    egui::Grid::new("settings_members")
+                        .num_columns(6)
+                        .show(ui, |ui| {
+                            for member in self.meal_planner.get_members_mut() {
+                                ui.text_edit_singleline(&mut member.name);
+                                let targets = &mut member.targets;
+                                ui.add(
+                                    DragValue::new(&mut targets.calories)
+                                        .range(0..=10000)
+                                        .suffix(" kcal"),
+                                );
+                                ui.add(
+                                    DragValue::new(&mut targets.protein)
+                                        .range(0..=1000)
+                                        .prefix("P ")
+                                        .suffix("g"),
+                                );
+                                ui.add(
+                                    DragValue::new(&mut targets.carbs)
+                                        .range(0..=1000)
+                                        .prefix("C ")
+                                        .suffix("g"),
+                                );
+                                ui.add(
+                                    DragValue::new(&mut targets.fat)
+                                        .range(0..=1000)
+                                        .prefix("F ")
+                                        .suffix("g"),
+                                );
+                                if ui
+                                    .button(ICON_TRASH_2)
+                                    .on_hover_text("Remove member and their portions")
+                                    .clicked()
+                                {
+                                    remove_member = Some(member.id);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    if let Some(member) = remove_member {
+                        self.meal_planner.remove_member(&member);
+                    }
+                    if ui.button("Add member").clicked() {
+                        self.meal_planner.add_member("New member");
+                    }
+                });
             });
 
         // Welcome screen
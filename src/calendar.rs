@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, NaiveTime, Utc};
+use egui::{DragValue, Grid};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    meal_planner::MealPlanner,
+    models::Recipe,
+    plan::PlanEntry,
+    typography::icons::ICON_CALENDAR_ARROW_DOWN,
+    util::{hb, hs, save_file, DEFAULT_PADDING},
+};
+
+/// iCalendar lines should not be longer than this many bytes.
+const LINE_LIMIT: usize = 75;
+const ICS_DATE_TIME: &str = "%Y%m%dT%H%M%S";
+
+/// Time of day, in minutes, a meal slot starts at when it has no time set.
+fn default_slot_time(slot_name: &str) -> u32 {
+    match slot_name.to_lowercase().as_str() {
+        "breakfast" => 8 * 60,
+        "lunch" => 12 * 60 + 30,
+        "dinner" => 19 * 60,
+        "snack" | "snacks" => 16 * 60,
+        _ => 12 * 60,
+    }
+}
+
+/// Escapes text for an iCalendar property value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line so no line is longer than [`LINE_LIMIT`] bytes, without
+/// splitting a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / LINE_LIMIT * 3);
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn description(recipe: &Recipe, entry: &PlanEntry) -> String {
+    let ingredients = recipe
+        .ingredients
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("- {}", line))
+        .collect::<Vec<String>>()
+        .join("\n");
+    format!(
+        "Portion: {}\n\nIngredients:\n{}\n\nInstructions:\n{}",
+        entry.portion.label(),
+        ingredients,
+        recipe.instructions.trim()
+    )
+}
+
+/// Exports the plan as iCalendar events, one per meal.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarExport {
+    /// Start of each meal slot, by slot name, in minutes after midnight.
+    slot_times: HashMap<String, u32>,
+    duration_minutes: u32,
+}
+
+impl Default for CalendarExport {
+    fn default() -> Self {
+        Self {
+            slot_times: HashMap::new(),
+            duration_minutes: 45,
+        }
+    }
+}
+
+impl CalendarExport {
+    fn slot_time(&self, slot_name: &str) -> u32 {
+        self.slot_times
+            .get(slot_name)
+            .copied()
+            .unwrap_or_else(|| default_slot_time(slot_name))
+    }
+
+    /// The plan as an iCalendar file. Event UIDs are made of the recipe id, the date
+    /// and the meal slot, so exporting again updates the events already imported.
+    pub fn to_ics(&self, meal_planner: &MealPlanner) -> String {
+        let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//meal-planner-egui//Meal Plan//EN".to_string(),
+            "CALSCALE:GREGORIAN".to_string(),
+            "X-WR-CALNAME:Meal plan".to_string(),
+        ];
+
+        for (day_idx, day) in meal_planner.get_daily_plan().iter().enumerate() {
+            let date = meal_planner.plan_day_date(day_idx);
+            for (slot_idx, slot) in day.slots.iter().enumerate() {
+                let slot_name = meal_planner
+                    .get_meal_slots()
+                    .get(slot_idx)
+                    .cloned()
+                    .unwrap_or_default();
+                let minutes = self.slot_time(&slot_name);
+                let time =
+                    NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0).unwrap_or_default();
+                let start = NaiveDateTime::new(date, time);
+                let end = start + chrono::Duration::minutes(self.duration_minutes as i64);
+
+                for (row_idx, entry) in slot.iter().enumerate() {
+                    let Some(recipe) = meal_planner.get_recipe_by_id(&entry.recipe_id) else {
+                        continue;
+                    };
+                    // The same recipe twice in a slot still needs two distinct UIDs.
+                    let repeat = slot[..row_idx]
+                        .iter()
+                        .filter(|other| other.recipe_id == entry.recipe_id)
+                        .count();
+                    let uid = format!(
+                        "{}-{}-{}-{}@meal-planner",
+                        recipe.id,
+                        date.format("%Y%m%d"),
+                        slot_idx,
+                        repeat
+                    );
+                    lines.extend([
+                        "BEGIN:VEVENT".to_string(),
+                        format!("UID:{}", uid),
+                        format!("DTSTAMP:{}", stamp),
+                        format!("DTSTART:{}", start.format(ICS_DATE_TIME)),
+                        format!("DTEND:{}", end.format(ICS_DATE_TIME)),
+                        format!("SUMMARY:{}", escape(&recipe.to_string())),
+                        format!("CATEGORIES:{}", escape(&slot_name)),
+                        format!("DESCRIPTION:{}", escape(&description(recipe, entry))),
+                        "END:VEVENT".to_string(),
+                    ]);
+                }
+            }
+        }
+        lines.push("END:VCALENDAR".to_string());
+
+        lines.iter().map(|line| fold(line)).collect()
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &MealPlanner) {
+        ui.label(hb("Meal times"));
+        Grid::new("calendar_meal_times")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for slot_name in meal_planner.get_meal_slots() {
+                    let minutes = self.slot_time(slot_name);
+                    let (mut hours, mut mins) = (minutes / 60, minutes % 60);
+                    ui.label(slot_name);
+                    ui.horizontal(|ui| {
+                        ui.spacing_mut().item_spacing.x = 2.;
+                        let hours_changed = ui
+                            .add(
+                                DragValue::new(&mut hours)
+                                    .range(0..=23)
+                                    .custom_formatter(|value, _| format!("{:02}", value as u32)),
+                            )
+                            .changed();
+                        ui.label(":");
+                        let mins_changed = ui
+                            .add(
+                                DragValue::new(&mut mins)
+                                    .range(0..=59)
+                                    .speed(0.5)
+                                    .custom_formatter(|value, _| format!("{:02}", value as u32)),
+                            )
+                            .changed();
+                        if hours_changed || mins_changed {
+                            self.slot_times.insert(slot_name.clone(), hours * 60 + mins);
+                        }
+                    });
+                    ui.end_row();
+                }
+                ui.label("Meal duration");
+                ui.add(
+                    DragValue::new(&mut self.duration_minutes)
+                        .range(5..=240)
+                        .suffix(" min"),
+                );
+                ui.end_row();
+            });
+
+        ui.add_space(DEFAULT_PADDING);
+        let first = meal_planner.plan_day_date(0);
+        let last = meal_planner.plan_day_date(meal_planner.plan_length() - 1);
+        ui.label(hs(&format!(
+            "Exports every planned meal from {} to {}. Importing the file again updates the events instead of duplicating them.",
+            first.format("%-d %b"),
+            last.format("%-d %b %Y")
+        )));
+        if ui
+            .button(format!("{} Export .ics", ICON_CALENDAR_ARROW_DOWN))
+            .clicked()
+        {
+            save_file("meal-plan.ics", self.to_ics(meal_planner).into_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(
+            escape("Salt, pepper; oil\\vinegar\r\nserve\nhot"),
+            r"Salt\, pepper\; oil\\vinegar\nserve\nhot"
+        );
+    }
+
+    #[test]
+    fn fold_long_lines_without_splitting_characters() {
+        let line = format!("SUMMARY:{}", "é".repeat(40));
+        let folded = fold(&line);
+        let lines: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() <= LINE_LIMIT));
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(lines.concat().replacen(' ', "", 1), line);
+        assert_eq!(fold("END:VEVENT"), "END:VEVENT\r\n");
+    }
+
+    #[test]
+    fn uids_are_stable_and_distinct() {
+        let mut meal_planner = MealPlanner::default();
+        let recipe_id = meal_planner.create_draft_recipe().unwrap().id;
+        meal_planner.add_recipe_to_planner(0, 0, 0, PlanEntry::new(recipe_id));
+        meal_planner.add_recipe_to_planner(0, 0, 1, PlanEntry::new(recipe_id));
+        meal_planner.add_recipe_to_planner(1, 0, 0, PlanEntry::new(recipe_id));
+        let export = CalendarExport::default();
+        let uids = |ics: &str| -> Vec<String> {
+            ics.lines()
+                .filter_map(|line| line.strip_prefix("UID:"))
+                .map(str::to_string)
+                .collect()
+        };
+        let first = uids(&export.to_ics(&meal_planner));
+        assert_eq!(first.len(), 3);
+        assert!(first
+            .iter()
+            .all(|uid| uid.starts_with(&recipe_id.to_string())));
+        assert_ne!(first[0], first[1]);
+        assert_ne!(first[0], first[2]);
+        assert_eq!(uids(&export.to_ics(&meal_planner)), first);
+    }
+}
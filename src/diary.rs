@@ -0,0 +1,662 @@
+use chrono::{Local, NaiveDate};
+use egui::{Button, DragValue, Grid, ScrollArea};
+use egui_extras::{Column, DatePickerButton, TableBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    meal_planner::MealPlanner,
+    models::Macros,
+    plan::{DayPlan, PlanEntry},
+    typography::icons::{
+        ICON_CHEVRON_LEFT, ICON_CHEVRON_RIGHT, ICON_CIRCLE_CHECK, ICON_CIRCLE_X, ICON_PLUS,
+        ICON_REPLACE, ICON_TRASH_2,
+    },
+    util::{hb, hs, DEFAULT_PADDING},
+};
+
+/// A food eaten that isn't one of the recipes.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AdHocFood {
+    pub name: String,
+    pub macros: Macros,
+}
+
+/// What was actually eaten in place of a planned meal.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Eaten {
+    AsPlanned,
+    Skipped,
+    Recipe(PlanEntry),
+    Food(AdHocFood),
+}
+
+/// Title and nutrition of a recipe portion when it was recorded, so that editing
+/// or deleting the recipe later doesn't change past days.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MealSnapshot {
+    pub title: String,
+    pub macros: Macros,
+}
+
+impl MealSnapshot {
+    pub fn new(meal_planner: &MealPlanner, entry: &PlanEntry) -> Self {
+        match meal_planner.get_recipe_by_id(&entry.recipe_id) {
+            Some(recipe) => Self {
+                title: recipe.to_string(),
+                macros: recipe
+                    .macros_per_serving()
+                    .scaled(entry.portion.servings(recipe)),
+            },
+            None => Self {
+                title: "Deleted recipe".to_string(),
+                macros: Macros::default(),
+            },
+        }
+    }
+}
+
+/// One meal of a diary day: what was planned and what was eaten.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DiaryRecord {
+    pub slot: String,
+    /// `None` for foods eaten outside of the plan.
+    pub planned: Option<PlanEntry>,
+    /// The planned recipe as it was when the day was recorded.
+    #[serde(default)]
+    pub planned_meal: Option<MealSnapshot>,
+    /// `None` until the meal is confirmed, skipped or replaced.
+    pub eaten: Option<Eaten>,
+    /// The recipe eaten instead of the planned meal, as it was when recorded.
+    #[serde(default)]
+    pub eaten_meal: Option<MealSnapshot>,
+}
+
+/// The meals of one calendar day, as planned at the time and as eaten.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiaryDay {
+    pub records: Vec<DiaryRecord>,
+}
+
+fn entry_label(entry: &PlanEntry, meal: Option<&MealSnapshot>) -> String {
+    let title = meal.map_or("Deleted recipe", |meal| meal.title.as_str());
+    if entry.portion.is_single_serving() {
+        title.to_string()
+    } else {
+        format!("{} ({})", title, entry.portion.label())
+    }
+}
+
+impl DiaryRecord {
+    /// Takes a snapshot of the recipes of the record that don't have one yet.
+    pub fn snapshot(&mut self, meal_planner: &MealPlanner) {
+        if self.planned_meal.is_none() {
+            self.planned_meal = self
+                .planned
+                .as_ref()
+                .map(|entry| MealSnapshot::new(meal_planner, entry));
+        }
+        self.eaten_meal = match &self.eaten {
+            Some(Eaten::Recipe(entry)) => self
+                .eaten_meal
+                .take()
+                .or_else(|| Some(MealSnapshot::new(meal_planner, entry))),
+            _ => None,
+        };
+    }
+
+    pub fn planned_macros(&self) -> Macros {
+        self.planned_meal
+            .as_ref()
+            .map(|meal| meal.macros)
+            .unwrap_or_default()
+    }
+
+    /// Unconfirmed meals count as eaten the way they were planned.
+    pub fn eaten_macros(&self) -> Macros {
+        match &self.eaten {
+            None | Some(Eaten::AsPlanned) => self.planned_macros(),
+            Some(Eaten::Skipped) => Macros::default(),
+            Some(Eaten::Recipe(_)) => self
+                .eaten_meal
+                .as_ref()
+                .map(|meal| meal.macros)
+                .unwrap_or_default(),
+            Some(Eaten::Food(food)) => food.macros,
+        }
+    }
+}
+
+impl DiaryDay {
+    /// A diary day with every meal of a plan day still to be confirmed.
+    pub fn from_plan(day: &DayPlan, meal_planner: &MealPlanner) -> Self {
+        let slot_names = meal_planner.get_meal_slots();
+        let records = day
+            .slots
+            .iter()
+            .enumerate()
+            .flat_map(|(slot_idx, slot)| {
+                let slot_name = slot_names.get(slot_idx).cloned().unwrap_or_default();
+                slot.iter().map(move |entry| DiaryRecord {
+                    slot: slot_name.clone(),
+                    planned: Some(entry.clone()),
+                    planned_meal: Some(MealSnapshot::new(meal_planner, entry)),
+                    eaten: None,
+                    eaten_meal: None,
+                })
+            })
+            .collect();
+        Self { records }
+    }
+
+    pub fn planned_macros(&self) -> Macros {
+        self.records
+            .iter()
+            .fold(Macros::default(), |total, record| {
+                total + record.planned_macros()
+            })
+    }
+
+    pub fn eaten_macros(&self) -> Macros {
+        self.records
+            .iter()
+            .fold(Macros::default(), |total, record| {
+                total + record.eaten_macros()
+            })
+    }
+
+    pub fn unconfirmed(&self) -> usize {
+        self.records
+            .iter()
+            .filter(|record| record.eaten.is_none())
+            .count()
+    }
+}
+
+/// Where the ad-hoc food form puts the food it creates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FoodTarget {
+    Replace(usize),
+    Extra,
+}
+
+/// Records what was eaten on past days and compares it with the plan.
+#[derive(Debug)]
+pub struct FoodDiary {
+    date: NaiveDate,
+    food_target: Option<FoodTarget>,
+    food: AdHocFood,
+}
+
+impl Default for FoodDiary {
+    fn default() -> Self {
+        Self {
+            date: Local::now().date_naive(),
+            food_target: None,
+            food: AdHocFood::default(),
+        }
+    }
+}
+
+/// "planned → eaten (difference)" text of one value.
+fn comparison_text(planned: f32, eaten: f32, unit: &str) -> String {
+    format!(
+        "{:.0} → {:.0}{} ({:+.0})",
+        planned,
+        eaten,
+        unit,
+        eaten - planned
+    )
+}
+
+impl FoodDiary {
+    pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        let today = Local::now().date_naive();
+
+        ui.horizontal(|ui| {
+            if ui.button(ICON_CHEVRON_LEFT).clicked() {
+                self.date = self.date.pred_opt().unwrap_or(self.date);
+                self.food_target = None;
+            }
+            ui.add(DatePickerButton::new(&mut self.date).id_salt("diary_date"));
+            if ui
+                .add_enabled(self.date < today, Button::new(ICON_CHEVRON_RIGHT))
+                .clicked()
+            {
+                self.date = self.date.succ_opt().unwrap_or(self.date);
+                self.food_target = None;
+            }
+            if let Some(day) = meal_planner.plan_day_for_date(self.date) {
+                ui.label(hs(&format!("Day {} of the plan", day + 1)));
+            }
+        });
+        if self.date > today {
+            ui.label("Meals can be recorded once their day has come.");
+            self.report_ui(ui, meal_planner);
+            return;
+        }
+
+        ui.add_space(DEFAULT_PADDING);
+        let diary_day = meal_planner.get_diary_day(self.date);
+        if diary_day.records.is_empty() {
+            ui.label(hs("Nothing was planned for this day."));
+        }
+        self.records_ui(ui, meal_planner, &diary_day);
+
+        ui.horizontal(|ui| {
+            ui.menu_button(format!("{} Add recipe", ICON_PLUS), |ui| {
+                if let Some(entry) = recipe_menu_ui(ui, meal_planner) {
+                    meal_planner.add_diary_record(
+                        self.date,
+                        DiaryRecord {
+                            slot: String::new(),
+                            planned: None,
+                            planned_meal: None,
+                            eaten: Some(Eaten::Recipe(entry)),
+                            eaten_meal: None,
+                        },
+                    );
+                    ui.close_menu();
+                }
+            });
+            if ui.button(format!("{} Add other food", ICON_PLUS)).clicked() {
+                self.food_target = Some(FoodTarget::Extra);
+                self.food = AdHocFood::default();
+            }
+        });
+
+        self.food_ui(ui, meal_planner);
+
+        let planned = diary_day.planned_macros();
+        let eaten = diary_day.eaten_macros();
+        ui.label(hs(&format!(
+            "Calories {}",
+            comparison_text(planned.calories, eaten.calories, " kcal")
+        )));
+
+        ui.add_space(DEFAULT_PADDING);
+        ui.separator();
+        self.report_ui(ui, meal_planner);
+    }
+
+    fn records_ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner, day: &DiaryDay) {
+        let date = self.date;
+        Grid::new("diary_records")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                for (idx, record) in day.records.iter().enumerate() {
+                    ui.label(hs(&record.slot));
+                    match &record.planned {
+                        Some(entry) => ui.label(entry_label(entry, record.planned_meal.as_ref())),
+                        None => ui.label(hs("Not planned")),
+                    };
+                    let eaten_text = match &record.eaten {
+                        None => "Not confirmed yet".to_string(),
+                        Some(Eaten::AsPlanned) => "Eaten".to_string(),
+                        Some(Eaten::Skipped) => "Skipped".to_string(),
+                        Some(Eaten::Recipe(entry)) => {
+                            entry_label(entry, record.eaten_meal.as_ref())
+                        }
+                        Some(Eaten::Food(food)) => {
+                            format!("{} ({:.0} kcal)", food.name, food.macros.calories)
+                        }
+                    };
+                    ui.label(hs(&eaten_text));
+
+                    ui.horizontal(|ui| {
+                        if record.planned.is_none() {
+                            if ui.button(ICON_TRASH_2).on_hover_text("Remove").clicked() {
+                                meal_planner.remove_diary_record(date, idx);
+                            }
+                            return;
+                        }
+                        let eaten = record.eaten == Some(Eaten::AsPlanned);
+                        if ui
+                            .selectable_label(eaten, ICON_CIRCLE_CHECK)
+                            .on_hover_text("Eaten as planned")
+                            .clicked()
+                        {
+                            meal_planner.record_eaten(date, idx, Some(Eaten::AsPlanned));
+                        }
+                        let skipped = record.eaten == Some(Eaten::Skipped);
+                        if ui
+                            .selectable_label(skipped, ICON_CIRCLE_X)
+                            .on_hover_text("Skipped")
+                            .clicked()
+                        {
+                            meal_planner.record_eaten(date, idx, Some(Eaten::Skipped));
+                        }
+                        ui.menu_button(ICON_REPLACE, |ui| {
+                            if ui.button("Other food…").clicked() {
+                                self.food_target = Some(FoodTarget::Replace(idx));
+                                self.food = AdHocFood::default();
+                                ui.close_menu();
+                            }
+                            ui.separator();
+                            if let Some(entry) = recipe_menu_ui(ui, meal_planner) {
+                                meal_planner.record_eaten(date, idx, Some(Eaten::Recipe(entry)));
+                                ui.close_menu();
+                            }
+                        })
+                        .response
+                        .on_hover_text("Replace with something else");
+                    });
+                    ui.end_row();
+                }
+            });
+    }
+
+    /// Form for a food that isn't one of the recipes.
+    fn food_ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        let Some(target) = self.food_target else {
+            return;
+        };
+        ui.group(|ui| {
+            ui.label(hb("Other food"));
+            Grid::new("diary_food").num_columns(2).show(ui, |ui| {
+                let macros = &mut self.food.macros;
+                ui.label("Name");
+                ui.text_edit_singleline(&mut self.food.name);
+                ui.end_row();
+                ui.label("Calories");
+                ui.add(
+                    DragValue::new(&mut macros.calories)
+                        .range(0..=10000)
+                        .suffix(" kcal"),
+                );
+                ui.end_row();
+                ui.label("Protein");
+                ui.add(
+                    DragValue::new(&mut macros.protein)
+                        .range(0..=1000)
+                        .suffix("g"),
+                );
+                ui.end_row();
+                ui.label("Carbohydrates");
+                ui.add(
+                    DragValue::new(&mut macros.carbs)
+                        .range(0..=1000)
+                        .suffix("g"),
+                );
+                ui.end_row();
+                ui.label("Fat");
+                ui.add(DragValue::new(&mut macros.fat).range(0..=1000).suffix("g"));
+                ui.end_row();
+            });
+            ui.horizontal(|ui| {
+                let save_btn = Button::new("Save");
+                if ui
+                    .add_enabled(!self.food.name.trim().is_empty(), save_btn)
+                    .clicked()
+                {
+                    let eaten = Eaten::Food(std::mem::take(&mut self.food));
+                    match target {
+                        FoodTarget::Replace(idx) => {
+                            meal_planner.record_eaten(self.date, idx, Some(eaten))
+                        }
+                        FoodTarget::Extra => meal_planner.add_diary_record(
+                            self.date,
+                            DiaryRecord {
+                                slot: String::new(),
+                                planned: None,
+                                planned_meal: None,
+                                eaten: Some(eaten),
+                                eaten_meal: None,
+                            },
+                        ),
+                    }
+                    self.food_target = None;
+                }
+                if ui.button("Cancel").clicked() {
+                    self.food_target = None;
+                }
+            });
+        });
+    }
+
+    /// Planned against eaten nutrition of every recorded day.
+    fn report_ui(&mut self, ui: &mut egui::Ui, meal_planner: &MealPlanner) {
+        ui.label(hb("Planned vs eaten"));
+        let days: Vec<(NaiveDate, Macros, Macros, usize)> = meal_planner
+            .get_diary()
+            .iter()
+            .map(|(date, day)| {
+                (
+                    *date,
+                    day.planned_macros(),
+                    day.eaten_macros(),
+                    day.unconfirmed(),
+                )
+            })
+            .collect();
+        if days.is_empty() {
+            ui.label(hs("Days show up here once you record a meal."));
+            return;
+        }
+        ui.label(hs(
+            "Unconfirmed meals count as eaten the way they were planned.",
+        ));
+
+        let count = days.len() as f32;
+        let (planned_total, eaten_total) = days.iter().fold(
+            (Macros::default(), Macros::default()),
+            |(planned_total, eaten_total), (_, planned, eaten, _)| {
+                (planned_total + *planned, eaten_total + *eaten)
+            },
+        );
+        let average = (
+            planned_total.scaled(1. / count),
+            eaten_total.scaled(1. / count),
+        );
+
+        ScrollArea::vertical()
+            .id_salt("diary_report")
+            .max_height(300.)
+            .show(ui, |ui| {
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .vscroll(false)
+                    .column(Column::auto().at_least(90.))
+                    .columns(Column::auto().at_least(120.), 4)
+                    .column(Column::remainder())
+                    .header(24., |mut header| {
+                        for title in ["Date", "Calories", "Protein", "Carbs", "Fat", ""] {
+                            header.col(|ui| {
+                                ui.strong(title);
+                            });
+                        }
+                    })
+                    .body(|mut body| {
+                        let rows = days
+                            .iter()
+                            .rev()
+                            .map(|(date, planned, eaten, unconfirmed)| {
+                                (
+                                    date.format("%a %-d %b").to_string(),
+                                    planned,
+                                    eaten,
+                                    *unconfirmed,
+                                )
+                            })
+                            .chain([("Average".to_string(), &average.0, &average.1, 0)]);
+                        for (label, planned, eaten, unconfirmed) in rows {
+                            body.row(20., |mut row| {
+                                row.col(|ui| {
+                                    ui.label(hs(&label));
+                                });
+                                let values = [
+                                    (planned.calories, eaten.calories, " kcal"),
+                                    (planned.protein, eaten.protein, "g"),
+                                    (planned.carbs, eaten.carbs, "g"),
+                                    (planned.fat, eaten.fat, "g"),
+                                ];
+                                for (planned, eaten, unit) in values {
+                                    row.col(|ui| {
+                                        ui.label(hs(&comparison_text(planned, eaten, unit)));
+                                    });
+                                }
+                                row.col(|ui| {
+                                    if unconfirmed > 0 {
+                                        ui.label(hs(&format!("{} unconfirmed", unconfirmed)));
+                                    }
+                                });
+                            });
+                        }
+                    });
+            });
+    }
+}
+
+/// Lists the recipes to pick one from, eaten as a single serving.
+fn recipe_menu_ui(ui: &mut egui::Ui, meal_planner: &MealPlanner) -> Option<PlanEntry> {
+    let mut recipes = meal_planner.get_recipes();
+    recipes.sort_by(|a, b| a.title.cmp(&b.title));
+    let mut picked = None;
+    ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+        for recipe in recipes {
+            if ui.button(recipe.to_string()).clicked() {
+                picked = Some(PlanEntry::new(recipe.id));
+            }
+        }
+    });
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::plan::Portion;
+
+    fn add_recipe(meal_planner: &mut MealPlanner, title: &str, calories: i32) -> Uuid {
+        let recipe = meal_planner.create_draft_recipe().unwrap();
+        recipe.title = title.to_string();
+        recipe.servings = 2;
+        recipe.macros.calories = calories;
+        let id = recipe.id;
+        meal_planner.delete_draft_recipe();
+        id
+    }
+
+    fn food(calories: f32) -> AdHocFood {
+        AdHocFood {
+            name: "Apple".to_string(),
+            macros: Macros {
+                calories,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn from_plan_lists_every_planned_meal() {
+        let mut meal_planner = MealPlanner::default();
+        let soup = add_recipe(&mut meal_planner, "Soup", 800);
+        let mut entry = PlanEntry::new(soup);
+        entry.portion = Portion::Servings(2.);
+        meal_planner.add_recipe_to_planner(0, 0, 0, PlanEntry::new(soup));
+        meal_planner.add_recipe_to_planner(0, 2, 0, entry.clone());
+
+        let day = DiaryDay::from_plan(&meal_planner.get_daily_plan()[0], &meal_planner);
+        let slots = meal_planner.get_meal_slots();
+        assert_eq!(day.records.len(), 2);
+        assert_eq!(day.records[0].slot, slots[0]);
+        assert_eq!(day.records[1].slot, slots[2]);
+        assert_eq!(day.records[1].planned, Some(entry));
+        assert_eq!(day.records[1].planned_meal.as_ref().unwrap().title, "Soup");
+        assert_eq!(day.unconfirmed(), 2);
+        assert_eq!(day.planned_macros().calories, 1200.);
+        assert_eq!(day.eaten_macros().calories, 1200.);
+    }
+
+    #[test]
+    fn eaten_totals_follow_what_was_recorded() {
+        let mut meal_planner = MealPlanner::default();
+        let soup = add_recipe(&mut meal_planner, "Soup", 800);
+        let cake = add_recipe(&mut meal_planner, "Cake", 1000);
+        for slot in 0..4 {
+            meal_planner.add_recipe_to_planner(0, slot, 0, PlanEntry::new(soup));
+        }
+        let date = meal_planner.plan_day_date(0);
+        meal_planner.record_eaten(date, 0, Some(Eaten::AsPlanned));
+        meal_planner.record_eaten(date, 1, Some(Eaten::Skipped));
+        meal_planner.record_eaten(date, 2, Some(Eaten::Recipe(PlanEntry::new(cake))));
+        meal_planner.record_eaten(date, 3, Some(Eaten::Food(food(90.))));
+
+        let day = meal_planner.get_diary_day(date);
+        assert_eq!(day.unconfirmed(), 0);
+        assert_eq!(day.planned_macros().calories, 1600.);
+        assert_eq!(day.eaten_macros().calories, 400. + 500. + 90.);
+        assert_eq!(day.records[2].eaten_meal.as_ref().unwrap().title, "Cake");
+
+        meal_planner.record_eaten(date, 2, Some(Eaten::Recipe(PlanEntry::new(cake))));
+        let day = meal_planner.get_diary_day(date);
+        assert_eq!(day.records[2].eaten, None);
+        assert_eq!(day.records[2].eaten_meal, None);
+        assert_eq!(day.eaten_macros().calories, 400. + 400. + 90.);
+    }
+
+    #[test]
+    fn recipe_edits_leave_recorded_days_alone() {
+        let mut meal_planner = MealPlanner::default();
+        let soup = add_recipe(&mut meal_planner, "Soup", 800);
+        let cake = add_recipe(&mut meal_planner, "Cake", 1000);
+        meal_planner.add_recipe_to_planner(0, 0, 0, PlanEntry::new(soup));
+        meal_planner.add_recipe_to_planner(1, 0, 0, PlanEntry::new(soup));
+        let (recorded, planned) = (meal_planner.plan_day_date(0), meal_planner.plan_day_date(1));
+        meal_planner.record_eaten(recorded, 0, Some(Eaten::AsPlanned));
+        meal_planner.add_diary_record(
+            recorded,
+            DiaryRecord {
+                slot: String::new(),
+                planned: None,
+                planned_meal: None,
+                eaten: Some(Eaten::Recipe(PlanEntry::new(cake))),
+                eaten_meal: None,
+            },
+        );
+        let before = meal_planner.get_diary_day(recorded);
+        assert_eq!(before.eaten_macros().calories, 900.);
+
+        meal_planner.edit_recipe(&soup, |recipe| {
+            recipe.title = "Thin soup".to_string();
+            recipe.servings = 4;
+        });
+        meal_planner.remove_recipe(&cake);
+        assert_eq!(meal_planner.get_diary_day(recorded), before);
+        assert_eq!(
+            meal_planner
+                .get_diary_day(planned)
+                .planned_macros()
+                .calories,
+            200.
+        );
+    }
+
+    #[test]
+    fn old_diaries_get_snapshots_on_import() {
+        let mut meal_planner = MealPlanner::default();
+        let soup = add_recipe(&mut meal_planner, "Soup", 800);
+        let record = DiaryRecord {
+            slot: "Lunch".to_string(),
+            planned: Some(PlanEntry::new(soup)),
+            planned_meal: None,
+            eaten: Some(Eaten::Recipe(PlanEntry::new(soup))),
+            eaten_meal: None,
+        };
+        let mut saved = serde_json::to_value(&meal_planner).unwrap();
+        let mut record = serde_json::to_value(&record).unwrap();
+        record.as_object_mut().unwrap().remove("planned_meal");
+        record.as_object_mut().unwrap().remove("eaten_meal");
+        saved["diary"] = serde_json::json!({ "2024-03-01": { "records": [record] } });
+
+        let json = serde_json::json!({ "meal_planner": saved }).to_string();
+        let mut imported = MealPlanner::default();
+        assert!(imported.import_json(&json));
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let day = imported.get_diary_day(date);
+        assert_eq!(day.planned_macros().calories, 400.);
+        assert_eq!(day.eaten_macros().calories, 400.);
+    }
+}
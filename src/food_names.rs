@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use egui::{Button, Grid};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    meal_planner::MealPlanner,
+    typography::icons::{ICON_PLUS, ICON_TRASH_2},
+    util::hs,
+};
+
+/// Names the same food goes by, to start the dictionary with.
+const DEFAULT_SYNONYMS: [(&str, &str); 14] = [
+    ("scallion", "green onion"),
+    ("spring onion", "green onion"),
+    ("garbanzo bean", "chickpea"),
+    ("courgette", "zucchini"),
+    ("aubergine", "eggplant"),
+    ("capsicum", "bell pepper"),
+    ("coriander leaves", "cilantro"),
+    ("fresh coriander", "cilantro"),
+    ("rocket", "arugula"),
+    ("icing sugar", "powdered sugar"),
+    ("confectioners sugar", "powdered sugar"),
+    ("minced beef", "ground beef"),
+    ("beef mince", "ground beef"),
+    ("prawn", "shrimp"),
+];
+
+/// Lowercase singular form of a food name, so "Tomatoes" and "tomato" are the same.
+pub fn normalize(name: &str) -> String {
+    let name = name
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase();
+    if let Some(stem) = name.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if let Some(stem) = name.strip_suffix("oes") {
+        format!("{}o", stem)
+    } else if ["ches", "shes", "sses", "xes"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+    {
+        name[..name.len() - 2].to_string()
+    } else if name.ends_with('s') && !["ss", "us", "is"].iter().any(|s| name.ends_with(s)) {
+        name[..name.len() - 1].to_string()
+    } else {
+        name
+    }
+}
+
+/// The user's dictionary of food names that mean the same thing, merged on the
+/// shopping list, in the pantry and in search.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FoodNames {
+    /// Name to use by normalized alias.
+    synonyms: BTreeMap<String, String>,
+}
+
+impl Default for FoodNames {
+    fn default() -> Self {
+        Self {
+            synonyms: DEFAULT_SYNONYMS
+                .iter()
+                .map(|(alias, name)| (normalize(alias), name.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl FoodNames {
+    /// The name a food is listed under, as the dictionary spells it.
+    pub fn display_name(&self, food: &str) -> String {
+        self.synonyms
+            .get(&normalize(food))
+            .cloned()
+            .unwrap_or_else(|| food.trim().to_string())
+    }
+
+    /// What identifies a food: foods with the same key are merged.
+    pub fn key(&self, food: &str) -> String {
+        normalize(&self.display_name(food))
+    }
+
+    pub fn same_food(&self, a: &str, b: &str) -> bool {
+        self.key(a) == self.key(b)
+    }
+
+    pub fn synonyms(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.synonyms.iter()
+    }
+
+    /// Lists `alias` as another name of `name`.
+    pub fn add(&mut self, alias: &str, name: &str) {
+        self.synonyms
+            .insert(normalize(alias), name.trim().to_string());
+    }
+
+    pub fn remove(&mut self, alias: &str) {
+        self.synonyms.remove(&normalize(alias));
+    }
+}
+
+/// Edits the food name dictionary.
+#[derive(Debug, Default, Clone)]
+pub struct FoodNamesView {
+    alias: String,
+    name: String,
+}
+
+impl FoodNamesView {
+    pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        ui.label(hs(
+            "Foods listed under another name are merged with it on the shopping list, in the pantry and in search. Plurals are merged anyway.",
+        ));
+        let mut remove = None;
+        egui::ScrollArea::vertical()
+            .id_salt("food_names")
+            .max_height(200.)
+            .show(ui, |ui| {
+                Grid::new("food_names")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (alias, name) in meal_planner.get_food_names().synonyms() {
+                            ui.label(alias.as_str());
+                            ui.label(hs(&format!("→ {}", name)));
+                            if ui
+                                .button(ICON_TRASH_2)
+                                .on_hover_text("Remove synonym")
+                                .clicked()
+                            {
+                                remove = Some(alias.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        if let Some(alias) = remove {
+            meal_planner.remove_food_name(&alias);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.alias)
+                    .hint_text("scallions")
+                    .desired_width(120.),
+            );
+            ui.label("is");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.name)
+                    .hint_text("green onion")
+                    .desired_width(120.),
+            );
+            let (alias, name) = (self.alias.trim(), self.name.trim());
+            let valid =
+                !alias.is_empty() && !name.is_empty() && normalize(alias) != normalize(name);
+            if ui
+                .add_enabled(valid, Button::new(format!("{} Add", ICON_PLUS)))
+                .clicked()
+            {
+                meal_planner.add_food_name(alias, name);
+                self.alias.clear();
+                self.name.clear();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_singularizes() {
+        assert_eq!(normalize("Tomatoes"), "tomato");
+        assert_eq!(normalize("berries"), "berry");
+        assert_eq!(normalize("dishes"), "dish");
+        assert_eq!(normalize("peaches"), "peach");
+        assert_eq!(normalize("boxes"), "box");
+        assert_eq!(normalize("glasses"), "glass");
+        assert_eq!(normalize("Eggs"), "egg");
+        assert_eq!(normalize("  Green   Onions "), "green onion");
+    }
+
+    #[test]
+    fn normalize_keeps_words_that_only_look_plural() {
+        assert_eq!(normalize("hummus"), "hummus");
+        assert_eq!(normalize("glass"), "glass");
+        assert_eq!(normalize("Swiss"), "swiss");
+        assert_eq!(normalize("tahini"), "tahini");
+        assert_eq!(normalize(""), "");
+    }
+
+    #[test]
+    fn synonyms_share_a_key() {
+        let names = FoodNames::default();
+        assert_eq!(names.display_name("Scallions"), "green onion");
+        assert_eq!(names.display_name(" Leek "), "Leek");
+        assert!(names.same_food("scallions", "Green Onion"));
+        assert!(names.same_food("prawns", "shrimp"));
+        assert!(!names.same_food("prawn", "onion"));
+    }
+
+    #[test]
+    fn added_synonyms_can_be_removed() {
+        let mut names = FoodNames::default();
+        assert!(!names.same_food("Pak choi", "bok choy"));
+        names.add("Pak Choi", " bok choy ");
+        assert!(names.same_food("pak choi", "Bok Choy"));
+        assert_eq!(names.display_name("pak choi"), "bok choy");
+
+        names.remove("Scallions");
+        assert!(!names.same_food("scallion", "green onion"));
+        names.remove("pak choi");
+        assert_eq!(names, {
+            let mut defaults = FoodNames::default();
+            defaults.remove("scallion");
+            defaults
+        });
+    }
+
+    #[test]
+    fn old_saves_get_the_default_dictionary() {
+        let mut meal_planner = MealPlanner::default();
+        meal_planner.add_food_name("pak choi", "bok choy");
+        let old = r#"{"meal_planner": {"api_key": "", "app_id": "", "recipies": {}, "daily_plan": [[]]}}"#;
+        assert!(meal_planner.import_json(old));
+        assert_eq!(meal_planner.get_food_names(), &FoodNames::default());
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{meal_planner::MealPlanner, plan::DayPlan};
+
+/// Servings below this are treated as nothing left, to ignore rounding noise.
+const SERVING_EPSILON: f32 = 0.01;
+
+/// How a cooking event is eaten over the plan.
+#[derive(Debug, Default)]
+pub struct BatchUsage {
+    pub cooked: f32,
+    /// Entries eating from the batch in plan order as (day, slot, row), with the
+    /// servings left after each of them.
+    pub entries: Vec<((usize, usize, usize), f32)>,
+}
+
+impl BatchUsage {
+    /// The entry the batch is cooked for.
+    pub fn cook_location(&self) -> Option<(usize, usize, usize)> {
+        self.entries.first().map(|(location, _)| *location)
+    }
+
+    /// Servings left after the last entry eating from the batch.
+    pub fn leftover(&self) -> f32 {
+        self.entries
+            .last()
+            .map_or(self.cooked, |(_, remaining)| *remaining)
+    }
+}
+
+/// What the planner shows for an entry linked to a cooking event.
+#[derive(Debug, PartialEq)]
+pub enum EntryBatchStatus {
+    /// The batch is cooked for this entry.
+    Cooks { servings: f32, remaining: f32 },
+    /// The entry eats leftovers of a batch cooked earlier.
+    Leftovers { remaining: f32 },
+    /// The leftovers ran out before this entry, by this many servings.
+    RunsOut { missing: f32 },
+}
+
+impl EntryBatchStatus {
+    pub fn is_warning(&self) -> bool {
+        matches!(self, EntryBatchStatus::RunsOut { .. })
+    }
+}
+
+/// Follows every cooking event through the plan, day by day and slot by slot.
+pub fn batch_usage(plan: &[DayPlan], meal_planner: &MealPlanner) -> HashMap<Uuid, BatchUsage> {
+    let mut usage: HashMap<Uuid, BatchUsage> = HashMap::new();
+    for (day_idx, day) in plan.iter().enumerate() {
+        for (slot_idx, slot) in day.slots.iter().enumerate() {
+            for (row_idx, entry) in slot.iter().enumerate() {
+                let Some(batch_id) = entry.batch else {
+                    continue;
+                };
+                let (Some(batch), Some(recipe)) = (
+                    meal_planner.get_batch(&batch_id),
+                    meal_planner.get_recipe_by_id(&entry.recipe_id),
+                ) else {
+                    continue;
+                };
+                let batch_usage = usage.entry(batch_id).or_insert_with(|| BatchUsage {
+                    cooked: batch.servings,
+                    entries: vec![],
+                });
+                let remaining = batch_usage.leftover() - meal_planner.entry_servings(entry, recipe);
+                batch_usage
+                    .entries
+                    .push(((day_idx, slot_idx, row_idx), remaining));
+            }
+        }
+    }
+    usage
+}
+
+/// Status of the entry at `location` within its batch.
+pub fn entry_status(
+    usage: &BatchUsage,
+    location: (usize, usize, usize),
+) -> Option<EntryBatchStatus> {
+    let position = usage
+        .entries
+        .iter()
+        .position(|(entry_location, _)| *entry_location == location)?;
+    let remaining = usage.entries[position].1;
+    let before = match position {
+        0 => usage.cooked,
+        _ => usage.entries[position - 1].1,
+    };
+    Some(if remaining < -SERVING_EPSILON {
+        // Only count what this entry misses, not the shortage of earlier ones.
+        EntryBatchStatus::RunsOut {
+            missing: (-remaining).min(before - remaining),
+        }
+    } else if position == 0 {
+        EntryBatchStatus::Cooks {
+            servings: usage.cooked,
+            remaining: remaining.max(0.),
+        }
+    } else {
+        EntryBatchStatus::Leftovers {
+            remaining: remaining.max(0.),
+        }
+    })
+}
+
+/// Servings of a batch that are cooked but never eaten in the plan.
+pub fn uneaten_servings(usage: &BatchUsage) -> Option<f32> {
+    let leftover = usage.leftover();
+    (leftover > SERVING_EPSILON).then_some(leftover)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::PlanEntry;
+
+    /// A planner eating one serving of a 4 serving batch on each of the first `days`.
+    fn batch_plan(days: usize) -> (MealPlanner, Uuid) {
+        let mut meal_planner = MealPlanner::default();
+        let recipe = meal_planner.create_draft_recipe().unwrap();
+        recipe.servings = 4;
+        let recipe_id = recipe.id;
+        for day in 0..days {
+            meal_planner.add_recipe_to_planner(day, 0, 0, PlanEntry::new(recipe_id));
+        }
+        meal_planner.start_batch(0, 0, 0);
+        let batch = meal_planner.get_plan_entry(0, 0, 0).unwrap().batch;
+        for day in 1..days {
+            meal_planner.link_batch(day, 0, 0, batch);
+        }
+        (meal_planner, batch.unwrap())
+    }
+
+    #[test]
+    fn leftovers_are_eaten_in_plan_order() {
+        let (meal_planner, batch) = batch_plan(3);
+        let usage = batch_usage(meal_planner.get_daily_plan(), &meal_planner);
+        let usage = &usage[&batch];
+        assert_eq!(usage.cook_location(), Some((0, 0, 0)));
+        assert_eq!(
+            entry_status(usage, (0, 0, 0)),
+            Some(EntryBatchStatus::Cooks {
+                servings: 4.,
+                remaining: 3.
+            })
+        );
+        assert_eq!(
+            entry_status(usage, (2, 0, 0)),
+            Some(EntryBatchStatus::Leftovers { remaining: 1. })
+        );
+        assert_eq!(entry_status(usage, (3, 0, 0)), None);
+        assert_eq!(uneaten_servings(usage), Some(1.));
+    }
+
+    #[test]
+    fn running_out_counts_only_what_each_entry_misses() {
+        let (mut meal_planner, batch) = batch_plan(3);
+        meal_planner.set_batch_servings(&batch, 1.);
+        let usage = batch_usage(meal_planner.get_daily_plan(), &meal_planner);
+        let usage = &usage[&batch];
+        for day in [1, 2] {
+            let status = entry_status(usage, (day, 0, 0)).unwrap();
+            assert_eq!(status, EntryBatchStatus::RunsOut { missing: 1. });
+            assert!(status.is_warning());
+        }
+        assert_eq!(uneaten_servings(usage), None);
+    }
+}
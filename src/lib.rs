@@ -1,13 +1,30 @@
 #![warn(clippy::all)]
 
+mod aisles;
 mod app;
+mod calendar;
+mod diary;
+mod food_names;
+mod leftovers;
 mod meal_planner;
 mod models;
+mod packages;
+mod pantry;
+mod pdf;
+mod plan;
+mod plan_generator;
 mod planner;
+mod prices;
+mod printout;
 mod recipe_editor;
 mod recipe_gallery;
+mod search;
+mod shopping_export;
 mod shopping_list;
+mod summary;
+mod templates;
 mod theme;
+mod units;
 mod util;
 pub use app::MealPlannerApp;
 pub use theme::*;
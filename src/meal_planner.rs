@@ -1,20 +1,68 @@
+use chrono::{Local, NaiveDate};
 use ehttp::Request;
-use log::{debug, error, warn};
+use log::{error, warn};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
     sync::{Arc, Mutex},
 };
 use uuid::Uuid;
 
-use crate::models::{AnalysisRequest, AnalysisResponse, Recipe};
+use crate::{
+    diary::{DiaryDay, DiaryRecord, Eaten, MealSnapshot},
+    food_names::FoodNames,
+    models::{AnalysisRequest, AnalysisResponse, Macros, Recipe},
+    packages::PackageSize,
+    pantry::PantryItem,
+    plan::{
+        default_meal_slots, CookingEvent, DayPlan, Member, PlanEntry, PlanTemplate, Portion, Share,
+        DEFAULT_MEAL_SLOTS,
+    },
+    prices::{self, Cost, FoodPrice},
+    search::SearchIndex,
+    shopping_list::ExtraItem,
+};
+
+pub const MIN_PLAN_DAYS: usize = 1;
+pub const MAX_PLAN_DAYS: usize = 14;
+const DEFAULT_PLAN_DAYS: usize = 6;
+/// Shopping list amounts differing by less than this fraction are the same.
+const SHOPPING_AMOUNT_TOLERANCE: f32 = 0.001;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct IncomingState {
     pub api_key: String,
     pub app_id: String,
     pub recipies: HashMap<Uuid, Recipe>,
-    pub daily_plan: Vec<Vec<Uuid>>,
+    pub daily_plan: Vec<DayPlan>,
+    #[serde(default = "default_meal_slots")]
+    pub meal_slots: Vec<String>,
+    #[serde(default = "default_targets")]
+    pub targets: Macros,
+    #[serde(default)]
+    pub templates: Vec<PlanTemplate>,
+    #[serde(default)]
+    pub batches: HashMap<Uuid, CookingEvent>,
+    #[serde(default)]
+    pub members: Vec<Member>,
+    #[serde(default = "today")]
+    pub plan_start: NaiveDate,
+    #[serde(default)]
+    pub diary: BTreeMap<NaiveDate, DiaryDay>,
+    #[serde(default)]
+    pub shopping_checks: HashMap<String, f32>,
+    #[serde(default)]
+    pub pantry: Vec<PantryItem>,
+    #[serde(default)]
+    pub shopping_extras: Vec<ExtraItem>,
+    #[serde(default)]
+    pub prices: Vec<FoodPrice>,
+    #[serde(default)]
+    pub weekly_budget: Option<f32>,
+    #[serde(default)]
+    pub package_sizes: Vec<PackageSize>,
+    #[serde(default)]
+    pub food_names: FoodNames,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,38 +70,107 @@ struct IncomingJSON {
     meal_planner: IncomingState,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 enum ApiRequest {
+    #[default]
     Idle,
     Requesting(Uuid),
-    Complete(Uuid, AnalysisResponse),
+    Complete(Uuid, Box<AnalysisResponse>),
     Error(Uuid, String),
 }
 
-impl Default for ApiRequest {
-    fn default() -> Self {
-        ApiRequest::Idle
-    }
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MealPlanner {
     pub api_key: String,
     pub app_id: String,
     recipies: HashMap<Uuid, Recipe>,
-    daily_plan: Vec<Vec<Uuid>>,
+    daily_plan: Vec<DayPlan>,
+    #[serde(default = "default_meal_slots")]
+    meal_slots: Vec<String>,
+    /// Daily calorie and macro targets used by the plan generator.
+    #[serde(default = "default_targets")]
+    pub targets: Macros,
+    #[serde(default)]
+    templates: Vec<PlanTemplate>,
+    /// Batch cooking events, referenced by plan entries.
+    #[serde(default)]
+    batches: HashMap<Uuid, CookingEvent>,
+    /// Household members sharing the plan. Without members the plan is for one person.
+    #[serde(default)]
+    members: Vec<Member>,
+    /// Date of the first plan day.
+    #[serde(default = "today")]
+    plan_start: NaiveDate,
+    /// What was actually eaten, by date.
+    #[serde(default)]
+    diary: BTreeMap<NaiveDate, DiaryDay>,
+    /// Shopping list items ticked off, with the amount the list needed when they were.
+    #[serde(default)]
+    shopping_checks: HashMap<String, f32>,
+    /// What is already at home.
+    #[serde(default)]
+    pantry: Vec<PantryItem>,
+    /// Items to buy that aren't in any recipe.
+    #[serde(default)]
+    shopping_extras: Vec<ExtraItem>,
+    /// What foods cost at the store.
+    #[serde(default)]
+    prices: Vec<FoodPrice>,
+    /// Spending limit per week of the plan, warned about when the plan goes over it.
+    #[serde(default)]
+    pub weekly_budget: Option<f32>,
+    /// Sizes foods are sold in, which the shopping list rounds up to.
+    #[serde(default)]
+    package_sizes: Vec<PackageSize>,
+    /// Names that mean the same food.
+    #[serde(default)]
+    food_names: FoodNames,
+    /// Cost of one serving by recipe, cleared when recipes, prices or food names change.
+    #[serde(skip)]
+    serving_costs: Mutex<HashMap<Uuid, Cost>>,
+    #[serde(skip)]
+    search_index: Mutex<SearchIndex>,
     #[serde(skip)]
     api_request: Arc<Mutex<ApiRequest>>,
     draft_recipe: Option<Uuid>,
 }
 
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn default_targets() -> Macros {
+    Macros {
+        calories: 2000.,
+        protein: 100.,
+        carbs: 250.,
+        fat: 70.,
+    }
+}
+
 impl Default for MealPlanner {
     fn default() -> Self {
         Self {
             api_key: String::new(),
             app_id: String::new(),
             recipies: HashMap::new(),
-            daily_plan: vec![vec![], vec![], vec![], vec![], vec![], vec![]],
+            daily_plan: vec![DayPlan::with_slots(DEFAULT_MEAL_SLOTS.len()); DEFAULT_PLAN_DAYS],
+            meal_slots: default_meal_slots(),
+            targets: default_targets(),
+            templates: vec![],
+            batches: HashMap::new(),
+            members: vec![],
+            plan_start: today(),
+            diary: BTreeMap::new(),
+            shopping_checks: HashMap::new(),
+            pantry: vec![],
+            shopping_extras: vec![],
+            prices: vec![],
+            weekly_budget: None,
+            package_sizes: vec![],
+            food_names: FoodNames::default(),
+            serving_costs: Mutex::new(HashMap::new()),
+            search_index: Mutex::new(SearchIndex::default()),
             api_request: Arc::new(Mutex::new(ApiRequest::Idle)),
             draft_recipe: None,
         }
@@ -61,13 +178,36 @@ impl Default for MealPlanner {
 }
 
 impl MealPlanner {
-    pub fn from_json(&mut self, json: &str) -> bool {
+    pub fn import_json(&mut self, json: &str) -> bool {
         let result = serde_json::from_str::<IncomingJSON>(json);
         if let Ok(state) = result {
             self.api_key = state.meal_planner.api_key;
             self.app_id = state.meal_planner.app_id;
             self.recipies = state.meal_planner.recipies;
             self.daily_plan = state.meal_planner.daily_plan;
+            self.meal_slots = state.meal_planner.meal_slots;
+            self.targets = state.meal_planner.targets;
+            self.templates = state.meal_planner.templates;
+            self.batches = state.meal_planner.batches;
+            self.members = state.meal_planner.members;
+            self.plan_start = state.meal_planner.plan_start;
+            self.diary = state.meal_planner.diary;
+            self.shopping_checks = state.meal_planner.shopping_checks;
+            self.pantry = state.meal_planner.pantry;
+            self.shopping_extras = state.meal_planner.shopping_extras;
+            self.prices = state.meal_planner.prices;
+            self.weekly_budget = state.meal_planner.weekly_budget;
+            self.package_sizes = state.meal_planner.package_sizes;
+            self.food_names = state.meal_planner.food_names;
+            // Diaries saved before meals were recorded with their nutrition take it
+            // from the recipes as they are now.
+            let mut diary = std::mem::take(&mut self.diary);
+            for record in diary.values_mut().flat_map(|day| day.records.iter_mut()) {
+                record.snapshot(self);
+            }
+            self.diary = diary;
+            self.normalize_plan();
+            self.invalidate_caches();
             true
         } else {
             println!("{:?}", result.err().unwrap());
@@ -77,40 +217,542 @@ impl MealPlanner {
 
     #[cfg(target_arch = "wasm32")]
     pub fn is_daily_plan_empty(&self) -> bool {
-        let mut is_empty = 0;
-        for day in &self.daily_plan {
-            is_empty += day.len();
+        self.daily_plan.iter().all(|day| day.is_empty())
+    }
+
+    /// Keeps every day in sync with the configured meal slots and the plan length
+    /// within the supported range, e.g. after loading an older or imported state.
+    pub fn normalize_plan(&mut self) {
+        if self.meal_slots.is_empty() {
+            self.meal_slots = default_meal_slots();
+        }
+        if self.daily_plan.len() > MAX_PLAN_DAYS {
+            warn!(
+                "Plan has {} days, only the first {} are kept",
+                self.daily_plan.len(),
+                MAX_PLAN_DAYS
+            );
+        }
+        let plan_length = self.daily_plan.len().clamp(MIN_PLAN_DAYS, MAX_PLAN_DAYS);
+        self.daily_plan.resize(plan_length, DayPlan::default());
+        let slot_count = self.meal_slots.len();
+        for day in &mut self.daily_plan {
+            day.resize_slots(slot_count);
+        }
+        self.prune_batches();
+    }
+
+    /// Drops cooking events no plan entry eats from anymore, and links to events
+    /// that don't exist.
+    fn prune_batches(&mut self) {
+        let batches = &self.batches;
+        for day in &mut self.daily_plan {
+            for entry in day.slots.iter_mut().flatten() {
+                if entry.batch.is_some_and(|id| !batches.contains_key(&id)) {
+                    entry.batch = None;
+                }
+            }
+        }
+        let used: HashSet<Uuid> = self
+            .daily_plan
+            .iter()
+            .flat_map(|day| day.entries())
+            .filter_map(|entry| entry.batch)
+            .collect();
+        self.batches.retain(|id, _| used.contains(id));
+    }
+
+    /// Drops what is computed from the recipes, prices and food names, after one
+    /// of them changed.
+    fn invalidate_caches(&mut self) {
+        self.serving_costs.get_mut().unwrap().clear();
+        *self.search_index.get_mut().unwrap() = SearchIndex::default();
+    }
+
+    pub fn get_meal_slots(&self) -> &[String] {
+        &self.meal_slots
+    }
+
+    pub fn add_meal_slot(&mut self, name: &str) {
+        self.meal_slots.push(name.to_string());
+        self.normalize_plan();
+    }
+
+    pub fn rename_meal_slot(&mut self, slot: usize, name: &str) {
+        if let Some(slot_name) = self.meal_slots.get_mut(slot) {
+            *slot_name = name.to_string();
+        }
+    }
+
+    /// Removes a meal slot. The recipes planned in it are kept in the neighbouring slot.
+    pub fn remove_meal_slot(&mut self, slot: usize) {
+        if self.meal_slots.len() < 2 || slot >= self.meal_slots.len() {
+            return;
+        }
+        self.meal_slots.remove(slot);
+        let template_days = self.templates.iter_mut().flat_map(|t| t.days.iter_mut());
+        for day in self.daily_plan.iter_mut().chain(template_days) {
+            day.remove_slot(slot);
         }
-        is_empty == 0
     }
 
     pub fn is_api_configured(&self) -> bool {
         !self.api_key.is_empty() && !self.app_id.is_empty()
     }
 
+    pub fn plan_length(&self) -> usize {
+        self.daily_plan.len()
+    }
+
+    /// Sets the number of planned days. Shortening the plan drops the last days.
+    pub fn set_plan_length(&mut self, days: usize) {
+        let days = days.clamp(MIN_PLAN_DAYS, MAX_PLAN_DAYS);
+        self.daily_plan
+            .resize(days, DayPlan::with_slots(self.meal_slots.len()));
+        self.prune_batches();
+    }
+
     pub fn duplicate_day(&mut self, src_day: usize, dst_day: usize) {
-        let src_day_recipies = self.daily_plan.get(src_day);
-        let dst_day_recipies = src_day_recipies.cloned();
-        self.daily_plan[dst_day] = dst_day_recipies.unwrap();
+        if let Some(src_day_recipies) = self.daily_plan.get(src_day).cloned() {
+            if let Some(dst_day_recipies) = self.daily_plan.get_mut(dst_day) {
+                *dst_day_recipies = src_day_recipies;
+            }
+        }
     }
 
     pub fn remove_recipe(&mut self, recipe_id: &Uuid) {
         // Remove the recipe
         self.recipies.remove(recipe_id);
+        self.invalidate_caches();
+        self.batches
+            .retain(|_, batch| batch.recipe_id != *recipe_id);
 
-        // Update daily plan: remove references and adjust indices
-        for day in &mut self.daily_plan {
-            day.retain(|&meal_id| meal_id != *recipe_id);
+        // Update daily plan and templates: remove references and adjust indices
+        let template_days = self.templates.iter_mut().flat_map(|t| t.days.iter_mut());
+        for day in self.daily_plan.iter_mut().chain(template_days) {
+            for slot in &mut day.slots {
+                slot.retain(|entry| entry.recipe_id != *recipe_id);
+            }
+        }
+    }
+
+    pub fn get_templates(&self) -> &[PlanTemplate] {
+        &self.templates
+    }
+
+    /// Saves the days `from..=to` of the plan as a template. A template with the
+    /// same name is overwritten.
+    pub fn save_template(&mut self, name: &str, from: usize, to: usize) {
+        let to = to.min(self.daily_plan.len().saturating_sub(1));
+        if from > to {
+            return;
+        }
+        let days = self.daily_plan[from..=to].to_vec();
+        let batches = days
+            .iter()
+            .flat_map(|day| day.entries())
+            .filter_map(|entry| Some((entry.batch?, *self.batches.get(&entry.batch?)?)))
+            .collect();
+        let template = PlanTemplate {
+            name: name.to_string(),
+            days,
+            batches,
+        };
+        match self.templates.iter_mut().find(|t| t.name == name) {
+            Some(existing) => *existing = template,
+            None => self.templates.push(template),
+        }
+    }
+
+    pub fn get_members(&self) -> &[Member] {
+        &self.members
+    }
+
+    pub fn get_members_mut(&mut self) -> &mut [Member] {
+        &mut self.members
+    }
+
+    pub fn add_member(&mut self, name: &str) {
+        self.members.push(Member {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            targets: self.targets,
+        });
+    }
+
+    /// Removes a member along with what they were planned to eat.
+    pub fn remove_member(&mut self, member: &Uuid) {
+        self.members.retain(|m| m.id != *member);
+        let template_days = self.templates.iter_mut().flat_map(|t| t.days.iter_mut());
+        for day in self.daily_plan.iter_mut().chain(template_days) {
+            for entry in day.slots.iter_mut().flatten() {
+                entry.shares.retain(|share| share.member != *member);
+            }
+        }
+    }
+
+    /// Servings of an entry eaten by the whole household.
+    pub fn entry_servings(&self, entry: &PlanEntry, recipe: &Recipe) -> f32 {
+        if entry.shares.is_empty() {
+            entry.portion.servings(recipe) * self.members.len().max(1) as f32
+        } else {
+            entry
+                .shares
+                .iter()
+                .map(|share| share.portion.servings(recipe))
+                .sum()
+        }
+    }
+
+    /// Servings of an entry eaten by one member.
+    pub fn member_servings(&self, entry: &PlanEntry, member: &Uuid, recipe: &Recipe) -> f32 {
+        if entry.shares.is_empty() {
+            return entry.portion.servings(recipe);
+        }
+        entry
+            .shares
+            .iter()
+            .filter(|share| share.member == *member)
+            .map(|share| share.portion.servings(recipe))
+            .sum()
+    }
+
+    /// Sets how much a member eats of an entry, `None` taking them off it. The first
+    /// change turns "everyone eats the entry portion" into one share per member.
+    pub fn set_member_share(
+        &mut self,
+        (day, slot, recipe_position): (usize, usize, usize),
+        member: Uuid,
+        portion: Option<Portion>,
+    ) {
+        let member_ids: Vec<Uuid> = self.members.iter().map(|m| m.id).collect();
+        let Some(entry) = self.get_plan_entry_mut(day, slot, recipe_position) else {
+            return;
+        };
+        if entry.shares.is_empty() {
+            entry.shares = member_ids
+                .into_iter()
+                .map(|member| Share {
+                    member,
+                    portion: entry.portion,
+                })
+                .collect();
+        }
+        match entry.shares.iter_mut().find(|share| share.member == member) {
+            Some(share) => match portion {
+                Some(portion) => share.portion = portion,
+                None => entry.shares.retain(|share| share.member != member),
+            },
+            None => {
+                if let Some(portion) = portion {
+                    entry.shares.push(Share { member, portion });
+                }
+            }
+        }
+    }
+
+    pub fn get_plan_start(&self) -> NaiveDate {
+        self.plan_start
+    }
+
+    pub fn set_plan_start(&mut self, date: NaiveDate) {
+        self.plan_start = date;
+    }
+
+    pub fn plan_day_date(&self, day: usize) -> NaiveDate {
+        self.plan_start + chrono::Days::new(day as u64)
+    }
+
+    /// Index of the plan day falling on `date`, if the plan covers it.
+    pub fn plan_day_for_date(&self, date: NaiveDate) -> Option<usize> {
+        let day = usize::try_from((date - self.plan_start).num_days()).ok()?;
+        (day < self.daily_plan.len()).then_some(day)
+    }
+
+    pub fn get_diary(&self) -> &BTreeMap<NaiveDate, DiaryDay> {
+        &self.diary
+    }
+
+    /// The diary of a day. Until something is recorded it follows the plan.
+    pub fn get_diary_day(&self, date: NaiveDate) -> DiaryDay {
+        if let Some(day) = self.diary.get(&date) {
+            return day.clone();
+        }
+        self.plan_day_for_date(date)
+            .map(|day| DiaryDay::from_plan(&self.daily_plan[day], self))
+            .unwrap_or_default()
+    }
+
+    /// Diary of a day for recording, taking a snapshot of the plan the first time so
+    /// later plan changes don't rewrite history.
+    fn diary_day_mut(&mut self, date: NaiveDate) -> &mut DiaryDay {
+        if !self.diary.contains_key(&date) {
+            let day = self.get_diary_day(date);
+            self.diary.insert(date, day);
+        }
+        self.diary.get_mut(&date).unwrap()
+    }
+
+    /// Records what was eaten for a meal. Recording the same thing again undoes it.
+    pub fn record_eaten(&mut self, date: NaiveDate, record: usize, eaten: Option<Eaten>) {
+        let meal = match &eaten {
+            Some(Eaten::Recipe(entry)) => Some(MealSnapshot::new(self, entry)),
+            _ => None,
+        };
+        if let Some(record) = self.diary_day_mut(date).records.get_mut(record) {
+            if record.eaten == eaten {
+                record.eaten = None;
+                record.eaten_meal = None;
+            } else {
+                record.eaten = eaten;
+                record.eaten_meal = meal;
+            }
+        }
+    }
+
+    pub fn add_diary_record(&mut self, date: NaiveDate, mut record: DiaryRecord) {
+        record.snapshot(self);
+        self.diary_day_mut(date).records.push(record);
+    }
+
+    pub fn remove_diary_record(&mut self, date: NaiveDate, record: usize) {
+        let day = self.diary_day_mut(date);
+        if record < day.records.len() {
+            day.records.remove(record);
         }
     }
 
+    /// Whether a shopping list item is ticked off. Items needing a different amount
+    /// than when they were ticked count as not bought yet.
+    pub fn is_shopping_checked(&self, item: &str, amount: f32) -> bool {
+        self.shopping_checks.get(item).is_some_and(|checked| {
+            (checked - amount).abs() <= checked.abs().max(amount.abs()) * SHOPPING_AMOUNT_TOLERANCE
+        })
+    }
+
+    pub fn set_shopping_checked(&mut self, item: &str, amount: Option<f32>) {
+        match amount {
+            Some(amount) => self.shopping_checks.insert(item.to_string(), amount),
+            None => self.shopping_checks.remove(item),
+        };
+    }
+
+    pub fn reset_shopping_checks(&mut self) {
+        self.shopping_checks.clear();
+    }
+
+    pub fn get_shopping_extras(&self) -> &[ExtraItem] {
+        &self.shopping_extras
+    }
+
+    pub fn add_shopping_extra(&mut self, item: ExtraItem) {
+        self.shopping_extras.push(item);
+    }
+
+    pub fn remove_shopping_extra(&mut self, id: &Uuid) {
+        self.shopping_extras.retain(|item| item.id != *id);
+        self.shopping_checks.remove(&ExtraItem::key(id));
+    }
+
+    pub fn get_pantry(&self) -> &[PantryItem] {
+        &self.pantry
+    }
+
+    pub fn get_pantry_mut(&mut self) -> &mut [PantryItem] {
+        &mut self.pantry
+    }
+
+    /// Adds to the pantry, topping up what is already there of the same food when
+    /// the units allow it.
+    pub fn add_to_pantry(&mut self, item: PantryItem) {
+        let existing = self.pantry.iter_mut().find_map(|existing| {
+            let added = item.quantity.convert(&existing.quantity.unit)?;
+            existing
+                .is_food(&item.food, &self.food_names)
+                .then_some((existing, added))
+        });
+        match existing {
+            Some((existing, added)) => existing.quantity.amount += added.amount,
+            None => self.pantry.push(item),
+        }
+    }
+
+    pub fn remove_from_pantry(&mut self, id: &Uuid) {
+        self.pantry.retain(|item| item.id != *id);
+    }
+
+    pub fn get_prices(&self) -> &[FoodPrice] {
+        &self.prices
+    }
+
+    /// Adds a price, replacing the one of the same food.
+    pub fn set_price(&mut self, price: FoodPrice) {
+        let names = &self.food_names;
+        match self
+            .prices
+            .iter_mut()
+            .find(|p| p.is_food(&price.food, names))
+        {
+            Some(existing) => {
+                existing.quantity = price.quantity;
+                existing.cost = price.cost;
+            }
+            None => self.prices.push(price),
+        }
+        self.invalidate_caches();
+    }
+
+    pub fn remove_price(&mut self, id: &Uuid) {
+        self.prices.retain(|price| price.id != *id);
+        self.invalidate_caches();
+    }
+
+    /// Cost of one serving of a recipe, worked out once until recipes, prices or
+    /// food names change.
+    pub fn cost_per_serving(&self, recipe: &Recipe) -> Cost {
+        if let Some(cost) = self.serving_costs.lock().unwrap().get(&recipe.id) {
+            return cost.clone();
+        }
+        let cost = prices::cost_per_serving(recipe, self);
+        self.serving_costs
+            .lock()
+            .unwrap()
+            .insert(recipe.id, cost.clone());
+        cost
+    }
+
+    pub fn get_food_names(&self) -> &FoodNames {
+        &self.food_names
+    }
+
+    /// Lists `alias` as another name of the food `name`.
+    pub fn add_food_name(&mut self, alias: &str, name: &str) {
+        self.food_names.add(alias, name);
+        self.invalidate_caches();
+    }
+
+    pub fn remove_food_name(&mut self, alias: &str) {
+        self.food_names.remove(alias);
+        self.invalidate_caches();
+    }
+
+    pub fn get_package_sizes(&self) -> &[PackageSize] {
+        &self.package_sizes
+    }
+
+    pub fn add_package_size(&mut self, size: PackageSize) {
+        self.package_sizes.push(size);
+    }
+
+    pub fn remove_package_size(&mut self, id: &Uuid) {
+        self.package_sizes.retain(|size| size.id != *id);
+    }
+
+    pub fn get_batch(&self, batch: &Uuid) -> Option<&CookingEvent> {
+        self.batches.get(batch)
+    }
+
+    /// Turns an entry into a cooking event for the whole recipe. Entries linked to
+    /// it later on eat its leftovers.
+    pub fn start_batch(&mut self, day: usize, slot: usize, recipe_position: usize) {
+        let Some(entry) = self.get_plan_entry(day, slot, recipe_position) else {
+            return;
+        };
+        let Some(recipe) = self.get_recipe_by_id(&entry.recipe_id) else {
+            return;
+        };
+        let batch = CookingEvent {
+            recipe_id: recipe.id,
+            servings: (recipe.servings as f32).max(self.entry_servings(entry, recipe)),
+        };
+        let id = Uuid::new_v4();
+        self.batches.insert(id, batch);
+        if let Some(entry) = self.get_plan_entry_mut(day, slot, recipe_position) {
+            entry.batch = Some(id);
+        }
+    }
+
+    /// Links an entry to a cooking event of the same recipe, or unlinks it with `None`.
+    pub fn link_batch(
+        &mut self,
+        day: usize,
+        slot: usize,
+        recipe_position: usize,
+        batch: Option<Uuid>,
+    ) {
+        let batch_recipe = batch.map(|id| self.batches.get(&id).map(|b| b.recipe_id));
+        if let Some(entry) = self.get_plan_entry_mut(day, slot, recipe_position) {
+            match batch_recipe {
+                None => entry.batch = None,
+                Some(Some(recipe_id)) if recipe_id == entry.recipe_id => entry.batch = batch,
+                _ => {}
+            }
+        }
+        self.prune_batches();
+    }
+
+    pub fn set_batch_servings(&mut self, batch: &Uuid, servings: f32) {
+        if let Some(batch) = self.batches.get_mut(batch) {
+            batch.servings = servings.max(0.);
+        }
+    }
+
+    pub fn remove_template(&mut self, template: usize) {
+        if template < self.templates.len() {
+            self.templates.remove(template);
+        }
+    }
+
+    /// Applies a template to the days `from..=to`, repeating its days when the range
+    /// is longer than the template. With `replace` the days are cleared first,
+    /// otherwise the template meals are added to the ones already planned.
+    pub fn apply_template(&mut self, template: usize, from: usize, to: usize, replace: bool) {
+        let Some(template) = self.templates.get(template) else {
+            return;
+        };
+        if template.days.is_empty() {
+            return;
+        }
+        let slot_count = self.meal_slots.len();
+        let to = to.min(self.daily_plan.len().saturating_sub(1));
+        // Every repetition of the template cooks its own batches.
+        let mut batch_ids = HashMap::new();
+        for (offset, day_idx) in (from..=to).enumerate() {
+            if offset % template.days.len() == 0 {
+                batch_ids.clear();
+            }
+            let mut template_day = template.days[offset % template.days.len()].clone();
+            template_day.resize_slots(slot_count);
+            for entry in template_day.slots.iter_mut().flatten() {
+                entry.batch = entry.batch.and_then(|id| {
+                    let batch = template.batches.get(&id)?;
+                    let new_id = *batch_ids.entry(id).or_insert_with(|| {
+                        let new_id = Uuid::new_v4();
+                        self.batches.insert(new_id, *batch);
+                        new_id
+                    });
+                    Some(new_id)
+                });
+            }
+            let day = &mut self.daily_plan[day_idx];
+            if replace {
+                day.clear();
+            }
+            for (slot, entries) in day.slots.iter_mut().zip(template_day.slots) {
+                slot.extend(entries);
+            }
+        }
+        // Replaced meals may have been the last to eat from a cooking event.
+        self.prune_batches();
+    }
+
     pub fn lookup_nutrients_for_recipe_id(&mut self, ctx: &egui::Context, id: Uuid) {
         let request = self.api_request.clone();
         if ApiRequest::Idle != *request.lock().unwrap() {
             warn!("Pending request");
             return;
         }
-        *request.lock().unwrap() = ApiRequest::Requesting(id.clone());
+        *request.lock().unwrap() = ApiRequest::Requesting(id);
 
         let recipe = self.recipies.get(&id).unwrap();
         self.request(ctx, id, recipe.ingredients_to_vec());
@@ -120,7 +762,8 @@ impl MealPlanner {
         if let Ok(mut lock) = self.api_request.clone().try_lock() {
             match &*lock {
                 ApiRequest::Complete(uuid, analysis_response) => {
-                    self.recipies.get_mut(&uuid).unwrap().macros = analysis_response.to_owned();
+                    self.recipies.get_mut(uuid).unwrap().macros = *analysis_response.to_owned();
+                    self.invalidate_caches();
                     *lock = ApiRequest::Idle
                 }
                 ApiRequest::Error(uuid, err) => {
@@ -137,8 +780,9 @@ impl MealPlanner {
             return None;
         }
         let recipe = Recipe::default();
-        let id = recipe.id.clone();
+        let id = recipe.id;
         self.recipies.insert(recipe.id, recipe);
+        self.invalidate_caches();
         self.draft_recipe = Some(id);
         self.recipies.get_mut(&id)
     }
@@ -148,59 +792,158 @@ impl MealPlanner {
             let recipe = self.recipies.get(&id).unwrap();
             if recipe.title.trim().is_empty() {
                 self.recipies.remove(&id);
+                self.invalidate_caches();
             }
             self.draft_recipe = None;
         }
     }
 
+    /// Recipes matching `arg` anywhere in them, allowing for typos, best match first.
     pub fn search_recipe(&self, arg: &str) -> Vec<&Recipe> {
-        let result = self
-            .recipies
+        let mut index = self.search_index.lock().unwrap();
+        index.build(self.recipies.values(), &self.food_names);
+        index
+            .search(arg, &self.food_names)
             .iter()
-            .filter(|(_key, value)| value.title.to_lowercase().contains(&arg.to_lowercase()))
-            .map(|(_key, value)| value)
-            .collect::<Vec<&Recipe>>();
-        result
+            .filter_map(|id| self.recipies.get(id))
+            .collect()
     }
 
     pub fn get_recipes(&self) -> Vec<&Recipe> {
-        self.recipies.iter().map(|(_key, value)| value).collect()
+        self.recipies.values().collect()
     }
 
-    pub fn get_daily_plan(&self) -> &Vec<Vec<Uuid>> {
+    pub fn get_daily_plan(&self) -> &Vec<DayPlan> {
         self.daily_plan.as_ref()
     }
 
     pub fn clear_planner_day(&mut self, day: usize) {
         self.daily_plan.get_mut(day).unwrap().clear();
+        self.prune_batches();
     }
 
-    pub fn remove_planner_recipe(&mut self, day: usize, recipe_position: usize) -> Uuid {
-        self.daily_plan
-            .get_mut(day)
-            .unwrap()
-            .remove(recipe_position)
+    /// Removes an entry from the plan for good, along with its cooking event when
+    /// no other entry eats from it.
+    pub fn delete_planner_recipe(&mut self, day: usize, slot: usize, recipe_position: usize) {
+        if self
+            .remove_planner_recipe(day, slot, recipe_position)
+            .is_some()
+        {
+            self.prune_batches();
+        }
     }
 
-    pub fn add_recipe_to_planner(&mut self, day: usize, recipe_position: usize, recipe_id: Uuid) {
-        let day_plan = self.daily_plan.get_mut(day).unwrap();
+    /// Takes an entry out of the plan, or returns `None` if there is none at that
+    /// position.
+    pub fn remove_planner_recipe(
+        &mut self,
+        day: usize,
+        slot: usize,
+        recipe_position: usize,
+    ) -> Option<PlanEntry> {
+        let slot = self.daily_plan.get_mut(day)?.slots.get_mut(slot)?;
+        (recipe_position < slot.len()).then(|| slot.remove(recipe_position))
+    }
+
+    pub fn add_recipe_to_planner(
+        &mut self,
+        day: usize,
+        slot: usize,
+        recipe_position: usize,
+        entry: PlanEntry,
+    ) -> usize {
+        let day_plan = &mut self.daily_plan.get_mut(day).unwrap().slots[slot];
+
+        let insert_position = recipe_position.min(day_plan.len());
+
+        day_plan.insert(insert_position, entry);
+        insert_position
+    }
+
+    /// Plans meals in slots that are still empty, e.g. from a generated plan. Slots
+    /// planned in the meantime or no longer in the plan are left as they are.
+    pub fn fill_empty_slots(&mut self, meals: Vec<((usize, usize), Vec<PlanEntry>)>) {
+        for ((day, slot), entries) in meals {
+            if let Some(slot) = self
+                .daily_plan
+                .get_mut(day)
+                .and_then(|day| day.slots.get_mut(slot))
+                .filter(|slot| slot.is_empty())
+            {
+                *slot = entries;
+            }
+        }
+    }
 
-        let insert_position = if day_plan.len() == 0 {
-            day_plan.len()
-        } else if recipe_position > day_plan.len() - 1 {
-            day_plan.len()
+    /// Moves a plan entry to the end of the same meal slot on another day, or copies
+    /// it there when `keep_source` is set.
+    pub fn transfer_planner_recipe(
+        &mut self,
+        day: usize,
+        slot: usize,
+        recipe_position: usize,
+        to_day: usize,
+        keep_source: bool,
+    ) {
+        if to_day >= self.daily_plan.len() {
+            return;
+        }
+        let entry = if keep_source {
+            self.get_plan_entry(day, slot, recipe_position).cloned()
         } else {
-            recipe_position
+            self.remove_planner_recipe(day, slot, recipe_position)
         };
+        if let Some(entry) = entry {
+            self.add_recipe_to_planner(to_day, slot, usize::MAX, entry);
+        }
+    }
+
+    /// Inserts a copy of a plan entry right after it.
+    pub fn duplicate_planner_recipe(&mut self, day: usize, slot: usize, recipe_position: usize) {
+        if let Some(entry) = self.get_plan_entry(day, slot, recipe_position).cloned() {
+            self.add_recipe_to_planner(day, slot, recipe_position + 1, entry);
+        }
+    }
 
-        day_plan.insert(insert_position, recipe_id);
+    pub fn get_plan_entry(
+        &self,
+        day: usize,
+        slot: usize,
+        recipe_position: usize,
+    ) -> Option<&PlanEntry> {
+        self.daily_plan
+            .get(day)?
+            .slots
+            .get(slot)?
+            .get(recipe_position)
+    }
+
+    pub fn get_plan_entry_mut(
+        &mut self,
+        day: usize,
+        slot: usize,
+        recipe_position: usize,
+    ) -> Option<&mut PlanEntry> {
+        self.daily_plan
+            .get_mut(day)?
+            .slots
+            .get_mut(slot)?
+            .get_mut(recipe_position)
     }
 
     pub fn get_recipe_by_id(&self, id: &Uuid) -> Option<&Recipe> {
         self.recipies.get(id)
     }
-    pub fn get_recipe_by_id_mut(&mut self, id: &Uuid) -> Option<&mut Recipe> {
-        self.recipies.get_mut(id)
+    /// Lets `edit` change a recipe, dropping what was computed from the old one if
+    /// it does.
+    pub fn edit_recipe<R>(&mut self, id: &Uuid, edit: impl FnOnce(&mut Recipe) -> R) -> Option<R> {
+        let recipe = self.recipies.get_mut(id)?;
+        let before = recipe.clone();
+        let result = edit(recipe);
+        if *recipe != before {
+            self.invalidate_caches();
+        }
+        Some(result)
     }
 
     fn request(&mut self, ctx: &egui::Context, recipe_id: Uuid, ingr: Vec<String>) {
@@ -222,7 +965,7 @@ impl MealPlanner {
                         let maybe_deserialized = serde_json::from_str(raw_text);
                         if let Ok(deserialized) = maybe_deserialized {
                             *request.lock().unwrap() =
-                                ApiRequest::Complete(recipe_id, deserialized);
+                                ApiRequest::Complete(recipe_id, Box::new(deserialized));
                         } else {
                             error!(
                                 "Failed to deserialize API response: {}",
@@ -246,3 +989,223 @@ impl MealPlanner {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_loads_flat_days_into_first_slot() {
+        let id = Uuid::new_v4();
+        let json = format!(
+            r#"{{"meal_planner": {{"api_key": "", "app_id": "", "recipies": {{}},
+                "daily_plan": [["{id}"], []]}}}}"#
+        );
+        let mut planner = MealPlanner::default();
+        assert!(planner.import_json(&json));
+        assert_eq!(planner.get_meal_slots(), default_meal_slots());
+        let day = &planner.get_daily_plan()[0];
+        assert_eq!(day.slots.len(), DEFAULT_MEAL_SLOTS.len());
+        assert_eq!(day.slots[0], vec![PlanEntry::new(id)]);
+        assert!(planner.get_daily_plan()[1].is_empty());
+    }
+
+    #[test]
+    fn set_plan_length_drops_batches_of_removed_days() {
+        let mut planner = MealPlanner::default();
+        let recipe_id = Uuid::new_v4();
+        let (kept, dropped) = (Uuid::new_v4(), Uuid::new_v4());
+        for (id, day) in [(kept, 0), (dropped, DEFAULT_PLAN_DAYS - 1)] {
+            planner.batches.insert(
+                id,
+                CookingEvent {
+                    recipe_id,
+                    servings: 4.,
+                },
+            );
+            let mut entry = PlanEntry::new(recipe_id);
+            entry.batch = Some(id);
+            planner.daily_plan[day].slots[0].push(entry);
+        }
+        planner.set_plan_length(DEFAULT_PLAN_DAYS - 1);
+        assert!(planner.batches.contains_key(&kept));
+        assert!(!planner.batches.contains_key(&dropped));
+
+        planner.set_plan_length(0);
+        assert_eq!(planner.plan_length(), MIN_PLAN_DAYS);
+        planner.set_plan_length(MAX_PLAN_DAYS + 1);
+        assert_eq!(planner.plan_length(), MAX_PLAN_DAYS);
+    }
+
+    #[test]
+    fn remove_meal_slot_updates_templates() {
+        let mut planner = MealPlanner::default();
+        let (breakfast, lunch) = (
+            PlanEntry::new(Uuid::new_v4()),
+            PlanEntry::new(Uuid::new_v4()),
+        );
+        planner.daily_plan[0].slots[0].push(breakfast.clone());
+        planner.daily_plan[0].slots[1].push(lunch.clone());
+        planner.save_template("Monday", 0, 0);
+        planner.remove_meal_slot(1);
+        let merged = vec![breakfast, lunch];
+        assert_eq!(planner.daily_plan[0].slots[0], merged);
+        assert_eq!(planner.get_templates()[0].days[0].slots[0], merged);
+        assert_eq!(
+            planner.get_templates()[0].days[0].slots.len(),
+            DEFAULT_MEAL_SLOTS.len() - 1
+        );
+    }
+
+    #[test]
+    fn unused_batches_are_pruned() {
+        let mut planner = MealPlanner::default();
+        let recipe_id = planner.create_draft_recipe().unwrap().id;
+        planner.add_recipe_to_planner(0, 0, 0, PlanEntry::new(recipe_id));
+        planner.start_batch(0, 0, 0);
+        assert_eq!(planner.batches.len(), 1);
+        planner.link_batch(0, 0, 0, None);
+        assert!(planner.batches.is_empty());
+
+        planner.daily_plan[0].slots[0][0].batch = Some(Uuid::new_v4());
+        planner.normalize_plan();
+        assert_eq!(planner.daily_plan[0].slots[0][0].batch, None);
+
+        planner.start_batch(0, 0, 0);
+        planner.remove_recipe(&recipe_id);
+        assert!(planner.batches.is_empty());
+        assert!(planner.daily_plan.iter().all(|day| day.is_empty()));
+    }
+
+    #[test]
+    fn remove_planner_recipe_ignores_missing_entries() {
+        let mut planner = MealPlanner::default();
+        let entry = PlanEntry::new(Uuid::new_v4());
+        planner.add_recipe_to_planner(0, 1, 0, entry.clone());
+        assert_eq!(planner.remove_planner_recipe(0, 1, 1), None);
+        assert_eq!(
+            planner.remove_planner_recipe(0, DEFAULT_MEAL_SLOTS.len(), 0),
+            None
+        );
+        assert_eq!(planner.remove_planner_recipe(DEFAULT_PLAN_DAYS, 1, 0), None);
+        assert_eq!(planner.remove_planner_recipe(0, 1, 0), Some(entry));
+        assert_eq!(planner.remove_planner_recipe(0, 1, 0), None);
+    }
+
+    #[test]
+    fn shopping_checks_hold_for_the_checked_amount() {
+        let mut planner = MealPlanner::default();
+        planner.set_shopping_checked("tomato", Some(200.));
+        assert!(planner.is_shopping_checked("tomato", 200.));
+        assert!(!planner.is_shopping_checked("tomato", 300.));
+        assert!(!planner.is_shopping_checked("lime", 200.));
+        planner.set_shopping_checked("tomato", None);
+        assert!(!planner.is_shopping_checked("tomato", 200.));
+    }
+
+    #[test]
+    fn import_restores_targets() {
+        let mut planner = MealPlanner::default();
+        planner.targets.calories = 1500.;
+        let json = format!(
+            r#"{{"meal_planner": {}}}"#,
+            serde_json::to_string(&planner).unwrap()
+        );
+        let mut imported = MealPlanner::default();
+        assert!(imported.import_json(&json));
+        assert_eq!(imported.targets.calories, 1500.);
+
+        let old = r#"{"meal_planner": {"api_key": "", "app_id": "", "recipies": {}, "daily_plan": [[]]}}"#;
+        assert!(imported.import_json(old));
+        assert_eq!(imported.targets, default_targets());
+    }
+
+    #[test]
+    fn fill_empty_slots_keeps_planned_meals() {
+        let mut planner = MealPlanner::default();
+        let (kept, generated) = (
+            PlanEntry::new(Uuid::new_v4()),
+            PlanEntry::new(Uuid::new_v4()),
+        );
+        planner.daily_plan[0].slots[0].push(kept.clone());
+        planner.fill_empty_slots(vec![
+            ((0, 0), vec![generated.clone()]),
+            ((0, 1), vec![generated.clone()]),
+            ((DEFAULT_PLAN_DAYS, 0), vec![generated.clone()]),
+        ]);
+        assert_eq!(planner.daily_plan[0].slots[0], vec![kept]);
+        assert_eq!(planner.daily_plan[0].slots[1], vec![generated]);
+        assert_eq!(planner.plan_length(), DEFAULT_PLAN_DAYS);
+    }
+
+    #[test]
+    fn save_template_keeps_the_range_and_its_batches() {
+        let mut planner = MealPlanner::default();
+        let recipe_id = planner.create_draft_recipe().unwrap().id;
+        planner.add_recipe_to_planner(1, 0, 0, PlanEntry::new(recipe_id));
+        planner.add_recipe_to_planner(2, 1, 0, PlanEntry::new(recipe_id));
+        planner.start_batch(1, 0, 0);
+        planner.save_template("Two days", 1, 2);
+        planner.save_template("Past the end", 5, 99);
+        planner.save_template("Backwards", 2, 1);
+
+        assert_eq!(planner.templates.len(), 2);
+        let template = &planner.templates[0];
+        assert_eq!(template.days, planner.daily_plan[1..=2].to_vec());
+        assert_eq!(template.batches.len(), 1);
+        assert_eq!(planner.templates[1].days.len(), 1);
+
+        planner.clear_planner_day(2);
+        planner.save_template("Two days", 1, 2);
+        assert_eq!(planner.templates.len(), 2);
+        assert!(planner.templates[0].days[1].is_empty());
+    }
+
+    #[test]
+    fn apply_template_repeats_with_fresh_batches() {
+        let mut planner = MealPlanner::default();
+        let recipe_id = planner.create_draft_recipe().unwrap().id;
+        planner.add_recipe_to_planner(0, 0, 0, PlanEntry::new(recipe_id));
+        planner.add_recipe_to_planner(1, 0, 0, PlanEntry::new(recipe_id));
+        planner.start_batch(0, 0, 0);
+        planner.link_batch(1, 0, 0, planner.daily_plan[0].slots[0][0].batch);
+        planner.save_template("Cook once, eat twice", 0, 1);
+        let template_batch = planner.daily_plan[0].slots[0][0].batch;
+
+        planner.apply_template(0, 0, DEFAULT_PLAN_DAYS - 1, true);
+        let batches: Vec<Option<Uuid>> = planner
+            .daily_plan
+            .iter()
+            .map(|day| day.slots[0][0].batch)
+            .collect();
+        assert!(planner.daily_plan.iter().all(|day| day.slots[0].len() == 1));
+        for pair in batches.chunks(2) {
+            assert!(pair[0].is_some());
+            assert_eq!(pair[0], pair[1]);
+        }
+        assert_ne!(batches[0], batches[2]);
+        assert_ne!(batches[2], batches[4]);
+        assert!(!batches.contains(&template_batch));
+        assert_eq!(planner.batches.len(), DEFAULT_PLAN_DAYS / 2);
+    }
+
+    #[test]
+    fn apply_template_adds_or_replaces() {
+        let mut planner = MealPlanner::default();
+        let (kept, planned) = (Uuid::new_v4(), Uuid::new_v4());
+        planner.add_recipe_to_planner(0, 2, 0, PlanEntry::new(planned));
+        planner.save_template("Dinner", 0, 0);
+        planner.clear_planner_day(0);
+        planner.add_recipe_to_planner(1, 2, 0, PlanEntry::new(kept));
+        planner.add_recipe_to_planner(2, 2, 0, PlanEntry::new(kept));
+
+        planner.apply_template(0, 1, 1, false);
+        let recipes = |day: &DayPlan| -> Vec<Uuid> { day.entries().map(|e| e.recipe_id).collect() };
+        assert_eq!(recipes(&planner.daily_plan[1]), vec![kept, planned]);
+
+        planner.apply_template(0, 2, 2, true);
+        assert_eq!(recipes(&planner.daily_plan[2]), vec![planned]);
+        assert!(planner.daily_plan[0].is_empty());
+        assert!(planner.daily_plan[3].is_empty());
+    }
+}
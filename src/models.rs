@@ -15,6 +15,7 @@ const FOOD_LABEL_CODES: [&str; 9] = [
     "FAT", "FASAT", "FATRN", "CHOLE", "NA", "CHOCDF", "FIBTG", "SUGAR", "PROCNT",
 ];
 
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 pub struct ParsedNutrientSimple {
     pub qty: i32,
@@ -25,7 +26,7 @@ pub struct ParsedNutrientSimple {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-#[allow(non_snake_case)]
+#[allow(non_snake_case, dead_code)]
 pub struct NutritionDataModel {
     pub uri: String,
     pub calories: i32,
@@ -48,6 +49,7 @@ pub struct Nutrient {
     pub unit: String,
 }
 
+#[allow(dead_code)]
 impl Nutrient {
     pub fn qty_per_serving(&self, servings: u32) -> u32 {
         (self.quantity.abs().ceil() as u32) / servings
@@ -63,6 +65,45 @@ impl Nutrient {
     }
 }
 
+/// Calories and macronutrients, either as amounts eaten or as targets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Macros {
+    pub calories: f32,
+    pub protein: f32,
+    pub carbs: f32,
+    pub fat: f32,
+}
+
+impl Macros {
+    pub fn scaled(&self, factor: f32) -> Macros {
+        Macros {
+            calories: self.calories * factor,
+            protein: self.protein * factor,
+            carbs: self.carbs * factor,
+            fat: self.fat * factor,
+        }
+    }
+}
+
+impl std::ops::Add for Macros {
+    type Output = Macros;
+
+    fn add(self, rhs: Macros) -> Macros {
+        Macros {
+            calories: self.calories + rhs.calories,
+            protein: self.protein + rhs.protein,
+            carbs: self.carbs + rhs.carbs,
+            fat: self.fat + rhs.fat,
+        }
+    }
+}
+
+impl std::ops::AddAssign for Macros {
+    fn add_assign(&mut self, rhs: Macros) {
+        *self = *self + rhs;
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Ingredient {
     pub text: String,
@@ -77,6 +118,8 @@ pub struct ParsedNutrient {
     pub foodMatch: Option<String>,
     pub food: String,
     pub foodId: String,
+    #[serde(default)]
+    pub foodCategory: Option<String>,
     pub weight: f32,
     pub retainedWeight: f32,
     pub nutrients: HashMap<String, Nutrient>,
@@ -126,13 +169,8 @@ impl AnalysisResponseView {
         ui.separator();
         let id = format!("analysis_response_view_{}", ui.unique_id().value());
 
-        let mut show_nutrients = ui.data_mut(|data| {
-            if let Some(show) = data.get_temp::<bool>(id.clone().into()) {
-                show
-            } else {
-                false
-            }
-        });
+        let mut show_nutrients =
+            ui.data_mut(|data| data.get_temp::<bool>(id.clone().into()).unwrap_or_default());
 
         if ui.button("Nutrients").clicked() {
             show_nutrients = !show_nutrients;
@@ -321,7 +359,7 @@ impl AnalysisResponse {
     }
 }
 
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Recipe {
     pub id: Uuid,
     pub title: String,
@@ -355,11 +393,46 @@ impl Recipe {
             .collect()
     }
 
-    // produces the sum of 1 serving from each recipe
-    pub fn merge(&self, from: &Recipe) -> Recipe {
+    /// Quantity of a nutrient (by Edamam code) in one serving.
+    pub fn nutrient_per_serving(&self, code: &str) -> f32 {
+        self.macros
+            .totalNutrients
+            .get(code)
+            .map(|nutrient| nutrient.quantity / self.servings as f32)
+            .unwrap_or_default()
+    }
+
+    pub fn macros_per_serving(&self) -> Macros {
+        Macros {
+            calories: self.macros.calories as f32 / self.servings as f32,
+            protein: self.nutrient_per_serving("PROCNT"),
+            carbs: self.nutrient_per_serving("CHOCDF"),
+            fat: self.nutrient_per_serving("FAT"),
+        }
+    }
+
+    /// Short "kcal / protein / carbs / fat" summary of one serving.
+    pub fn macros_summary(&self) -> String {
+        format!(
+            "{} kcal · P {:.0}g · C {:.0}g · F {:.0}g",
+            self.macros.calories / self.servings as i32,
+            self.nutrient_per_serving("PROCNT"),
+            self.nutrient_per_serving("CHOCDF"),
+            self.nutrient_per_serving("FAT"),
+        )
+    }
+
+    /// Weight in grams of one serving, if the recipe has been analysed.
+    pub fn serving_weight(&self) -> f32 {
+        self.macros.totalWeight / self.servings as f32
+    }
+
+    // produces the sum of `servings` servings of this recipe and 1 serving of `from`
+    pub fn merge_portion(&self, servings: f32, from: &Recipe) -> Recipe {
         let mut merged = Recipe::default();
+        let scale = servings / self.servings as f32;
 
-        merged.macros.calories = self.macros.calories / self.servings as i32
+        merged.macros.calories = (self.macros.calories as f32 * scale).round() as i32
             + from.macros.calories / from.servings as i32;
 
         let default_nutrient = Nutrient::default();
@@ -398,7 +471,7 @@ impl Recipe {
                 code.to_string(),
                 Nutrient {
                     label: self_nutrient_gr.label.clone(),
-                    quantity: self_nutrient_gr.quantity / self.servings as f32
+                    quantity: self_nutrient_gr.quantity * scale
                         + from_nutrient_gr.quantity / from.servings as f32,
                     unit: self_nutrient_gr.unit.clone(),
                 },
@@ -408,7 +481,7 @@ impl Recipe {
                 code.to_string(),
                 Nutrient {
                     label: self_daily_nutrient.label.clone(),
-                    quantity: self_daily_nutrient.quantity / self.servings as f32
+                    quantity: self_daily_nutrient.quantity * scale
                         + from_nutrient_daily.quantity / from.servings as f32,
                     unit: self_daily_nutrient.unit.clone(),
                 },
@@ -418,17 +491,11 @@ impl Recipe {
     }
 }
 
-impl ToString for Recipe {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for Recipe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.title.is_empty() {
-            return "Default Recipe".to_string();
+            return write!(f, "Default Recipe");
         }
-        self.title.to_string()
-    }
-}
-
-impl ToString for &Recipe {
-    fn to_string(&self) -> String {
-        self.title.to_string()
+        write!(f, "{}", self.title)
     }
 }
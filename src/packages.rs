@@ -0,0 +1,311 @@
+use egui::{Button, DragValue, Grid};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    food_names::FoodNames,
+    meal_planner::MealPlanner,
+    typography::icons::{ICON_PLUS, ICON_TRASH_2},
+    units::{Quantity, Unit},
+    util::hs,
+};
+
+/// Most packages of one size tried when rounding up, which keeps the search short.
+const MAX_PACKAGES: usize = 20;
+
+/// Most combinations of packages tried when rounding up. Foods sold in many sizes
+/// would otherwise take too long to round up every frame.
+const MAX_COMBINATIONS: usize = 5000;
+
+/// A size a food is sold in, like a 200 g bag of spinach or a box of 6 eggs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PackageSize {
+    pub id: Uuid,
+    pub food: String,
+    pub quantity: Quantity,
+}
+
+impl PackageSize {
+    pub fn new(food: &str, quantity: Quantity) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            food: food.trim().to_string(),
+            quantity,
+        }
+    }
+
+    pub fn is_food(&self, food: &str, names: &FoodNames) -> bool {
+        names.same_food(&self.food, food)
+    }
+}
+
+/// Whole packages covering what the shopping list needs of a food.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Packing {
+    /// Number of packages of each size bought.
+    pub packages: Vec<(usize, Quantity)>,
+    /// Everything bought, in the unit of the shopping list item.
+    pub total: Quantity,
+    /// What is left once the plan is cooked.
+    pub leftover: Quantity,
+}
+
+impl Packing {
+    /// The cheapest way to cover `needed` with packages of `sizes`, each given as
+    /// (size in the unit of `needed`, size as sold): the least left over, then the
+    /// fewest packages. When no combination within the search covers `needed`, buys
+    /// enough of the largest size.
+    pub fn round_up(needed: &Quantity, sizes: &[(f32, Quantity)]) -> Option<Packing> {
+        let amounts: Vec<f32> = sizes.iter().map(|(amount, _)| *amount).collect();
+        if needed.amount <= 0. || amounts.iter().any(|amount| *amount <= 0.) {
+            return None;
+        }
+        let tolerance = needed.amount * 1e-4;
+        let mut best: Option<(Vec<usize>, f32)> = None;
+        let mut budget = MAX_COMBINATIONS;
+        fill(
+            needed.amount,
+            tolerance,
+            &amounts,
+            &mut vec![],
+            &mut best,
+            &mut budget,
+        );
+        let (counts, total) = best.or_else(|| largest_only(needed.amount, tolerance, &amounts))?;
+        let packages = counts
+            .into_iter()
+            .zip(sizes)
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, (_, size))| (count, size.clone()))
+            .collect();
+        Some(Packing {
+            packages,
+            total: Quantity::new(total, needed.unit.clone()),
+            leftover: Quantity::new((total - needed.amount).max(0.), needed.unit.clone())
+                .simplified(),
+        })
+    }
+
+    /// Whether there is enough left over to mention.
+    pub fn has_leftover(&self) -> bool {
+        self.leftover.amount >= 0.01
+    }
+}
+
+/// Enough packages of the largest of `sizes` to cover `needed`.
+fn largest_only(needed: f32, tolerance: f32, sizes: &[f32]) -> Option<(Vec<usize>, f32)> {
+    let (largest, size) = sizes
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    let count = (((needed - tolerance) / size).ceil() as usize).max(1);
+    let mut counts = vec![0; sizes.len()];
+    counts[largest] = count;
+    Some((counts, count as f32 * size))
+}
+
+/// Tries every number of packages of each size from `counts.len()` on, keeping in
+/// `best` the combination covering `needed` with the least bought, then the fewest
+/// packages. Gives up once `budget` combinations have been tried.
+fn fill(
+    needed: f32,
+    tolerance: f32,
+    sizes: &[f32],
+    counts: &mut Vec<usize>,
+    best: &mut Option<(Vec<usize>, f32)>,
+    budget: &mut usize,
+) {
+    let bought: f32 = counts
+        .iter()
+        .zip(sizes)
+        .map(|(count, size)| *count as f32 * size)
+        .sum();
+    let Some(&size) = sizes.get(counts.len()) else {
+        *budget = budget.saturating_sub(1);
+        if bought + tolerance < needed {
+            return;
+        }
+        let packages: usize = counts.iter().sum();
+        let better = best.as_ref().is_none_or(|(best_counts, best_bought)| {
+            let best_packages: usize = best_counts.iter().sum();
+            bought + tolerance < *best_bought
+                || (bought <= best_bought + tolerance && packages < best_packages)
+        });
+        if better {
+            *best = Some((counts.clone(), bought));
+        }
+        return;
+    };
+    let most = (((needed - bought).max(0.) / size).ceil() as usize).min(MAX_PACKAGES);
+    for count in 0..=most {
+        if *budget == 0 {
+            return;
+        }
+        counts.push(count);
+        fill(needed, tolerance, sizes, counts, best, budget);
+        counts.pop();
+    }
+}
+
+impl std::fmt::Display for Packing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let packages: Vec<String> = self
+            .packages
+            .iter()
+            .map(|(count, size)| format!("{} × {}", count, size))
+            .collect();
+        write!(f, "{}", packages.join(" + "))
+    }
+}
+
+/// Lists and adds the package sizes foods are sold in.
+#[derive(Debug, Clone)]
+pub struct PackageSizesView {
+    food: String,
+    amount: f32,
+    unit: String,
+}
+
+impl Default for PackageSizesView {
+    fn default() -> Self {
+        Self {
+            food: String::new(),
+            amount: 1.,
+            unit: String::new(),
+        }
+    }
+}
+
+impl PackageSizesView {
+    pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        ui.label(hs(
+            "The shopping list rounds foods up to whole packages of these sizes. Give a food more than one size to pick the best fit.",
+        ));
+        let mut remove = None;
+        Grid::new("package_sizes")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for size in meal_planner.get_package_sizes() {
+                    ui.label(&size.food);
+                    ui.label(size.quantity.to_string());
+                    if ui
+                        .button(ICON_TRASH_2)
+                        .on_hover_text("Remove package size")
+                        .clicked()
+                    {
+                        remove = Some(size.id);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(id) = remove {
+            meal_planner.remove_package_size(&id);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.food)
+                    .hint_text("Ingredient")
+                    .desired_width(120.),
+            );
+            ui.add(
+                DragValue::new(&mut self.amount)
+                    .range(0.0..=100000.)
+                    .speed(0.5),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut self.unit)
+                    .hint_text("unit")
+                    .desired_width(50.),
+            );
+            let food = self.food.trim();
+            if ui
+                .add_enabled(
+                    !food.is_empty() && self.amount > 0.,
+                    Button::new(format!("{} Add", ICON_PLUS)),
+                )
+                .clicked()
+            {
+                let quantity = Quantity::new(self.amount, Unit::parse(&self.unit));
+                meal_planner.add_package_size(PackageSize::new(food, quantity));
+                self.food.clear();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grams(sizes: &[f32]) -> Vec<(f32, Quantity)> {
+        sizes
+            .iter()
+            .map(|size| (*size, Quantity::grams(*size)))
+            .collect()
+    }
+
+    #[test]
+    fn round_up_prefers_least_leftover() {
+        let packing = Packing::round_up(&Quantity::grams(137.), &grams(&[200.])).unwrap();
+        assert_eq!(packing.packages, vec![(1, Quantity::grams(200.))]);
+        assert_eq!(packing.leftover, Quantity::grams(63.));
+        assert!(packing.has_leftover());
+
+        let packing = Packing::round_up(&Quantity::grams(650.), &grams(&[200., 500.])).unwrap();
+        assert_eq!(packing.total, Quantity::grams(700.));
+        assert_eq!(packing.to_string(), "1 × 200g + 1 × 500g");
+    }
+
+    #[test]
+    fn round_up_prefers_fewest_packages() {
+        let eggs = |amount| Quantity::new(amount, Unit::parse(""));
+        let sizes = [(6., eggs(6.)), (12., eggs(12.))];
+        let packing = Packing::round_up(&eggs(8.), &sizes).unwrap();
+        assert_eq!(packing.packages, vec![(1, eggs(12.))]);
+
+        let packing = Packing::round_up(&eggs(12.), &sizes).unwrap();
+        assert_eq!(packing.packages, vec![(1, eggs(12.))]);
+        assert!(!packing.has_leftover());
+    }
+
+    #[test]
+    fn round_up_rejects_empty_sizes_and_needs() {
+        let needed = Quantity::grams(100.);
+        assert_eq!(Packing::round_up(&needed, &grams(&[0.])), None);
+        assert_eq!(Packing::round_up(&needed, &grams(&[250., -50.])), None);
+        assert_eq!(Packing::round_up(&needed, &[]), None);
+        assert_eq!(
+            Packing::round_up(&Quantity::grams(0.), &grams(&[250.])),
+            None
+        );
+        assert_eq!(
+            Packing::round_up(&Quantity::grams(-5.), &grams(&[250.])),
+            None
+        );
+    }
+
+    #[test]
+    fn round_up_caps_package_count() {
+        let packing = Packing::round_up(&Quantity::grams(10000.), &grams(&[1., 5000.])).unwrap();
+        assert_eq!(packing.packages, vec![(2, Quantity::grams(5000.))]);
+
+        // Too many packages to search, so it buys the largest size.
+        let packing = Packing::round_up(&Quantity::grams(100.), &grams(&[1.])).unwrap();
+        assert_eq!(packing.packages, vec![(100, Quantity::grams(1.))]);
+        let packing = Packing::round_up(&Quantity::grams(2000.), &grams(&[10., 40.])).unwrap();
+        assert_eq!(packing.packages, vec![(50, Quantity::grams(40.))]);
+        assert!(!packing.has_leftover());
+    }
+
+    #[test]
+    fn round_up_stops_searching_many_sizes() {
+        let sizes = grams(&[
+            50., 75., 100., 125., 150., 175., 200., 250., 300., 400., 500., 750.,
+        ]);
+        // Trying every combination of twelve sizes would never finish.
+        let packing = Packing::round_up(&Quantity::grams(2000.), &sizes).unwrap();
+        assert!(packing.total.amount >= 2000.);
+    }
+}
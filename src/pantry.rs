@@ -0,0 +1,212 @@
+use chrono::{Local, NaiveDate};
+use egui::{Button, Color32, DragValue, Grid, RichText};
+use egui_extras::DatePickerButton;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    food_names::FoodNames,
+    meal_planner::MealPlanner,
+    typography::icons::{ICON_PLUS, ICON_TRASH_2},
+    units::{Quantity, Unit},
+    util::{hb, hs, DEFAULT_PADDING},
+};
+
+/// Days before its expiry date a pantry item is flagged.
+const EXPIRY_WARNING_DAYS: i64 = 3;
+
+/// Something already at home, which the shopping list doesn't need to buy.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PantryItem {
+    pub id: Uuid,
+    pub food: String,
+    pub quantity: Quantity,
+    pub expires: Option<NaiveDate>,
+}
+
+impl PantryItem {
+    pub fn new(food: &str, quantity: Quantity, expires: Option<NaiveDate>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            food: food.trim().to_string(),
+            quantity,
+            expires,
+        }
+    }
+
+    pub fn is_food(&self, food: &str, names: &FoodNames) -> bool {
+        names.same_food(&self.food, food)
+    }
+}
+
+/// Lists and edits the pantry.
+#[derive(Debug)]
+pub struct PantryView {
+    food: String,
+    amount: f32,
+    unit: String,
+    expires: Option<NaiveDate>,
+}
+
+impl Default for PantryView {
+    fn default() -> Self {
+        Self {
+            food: String::new(),
+            amount: 1.,
+            unit: String::new(),
+            expires: None,
+        }
+    }
+}
+
+fn expiry_ui(ui: &mut egui::Ui, expires: Option<NaiveDate>, today: NaiveDate) {
+    let Some(date) = expires else {
+        ui.label(hs("—"));
+        return;
+    };
+    let text = RichText::new(date.format("%-d %b %Y").to_string());
+    let days_left = (date - today).num_days();
+    if days_left < 0 {
+        ui.label(text.color(Color32::RED))
+            .on_hover_text("Past its expiry date");
+    } else if days_left <= EXPIRY_WARNING_DAYS {
+        ui.label(text.color(Color32::from_rgb(230, 140, 0)))
+            .on_hover_text("Expires soon");
+    } else {
+        ui.label(text);
+    }
+}
+
+impl PantryView {
+    fn add_ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        ui.label(hb("Add to pantry"));
+        Grid::new("pantry_add").num_columns(2).show(ui, |ui| {
+            ui.label("Ingredient");
+            ui.text_edit_singleline(&mut self.food);
+            ui.end_row();
+            ui.label("Quantity");
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut self.amount)
+                        .range(0.0..=100000.)
+                        .speed(0.5),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.unit)
+                        .hint_text("g, ml, tbsp, …")
+                        .desired_width(80.),
+                );
+            });
+            ui.end_row();
+            ui.label("Expires");
+            ui.horizontal(|ui| {
+                let mut has_expiry = self.expires.is_some();
+                if ui.checkbox(&mut has_expiry, "").changed() {
+                    self.expires = has_expiry.then(|| Local::now().date_naive());
+                }
+                if let Some(date) = self.expires.as_mut() {
+                    ui.add(DatePickerButton::new(date).id_salt("pantry_expires"));
+                }
+            });
+            ui.end_row();
+        });
+        let food = self.food.trim();
+        if ui
+            .add_enabled(
+                !food.is_empty() && self.amount > 0.,
+                Button::new(format!("{} Add", ICON_PLUS)),
+            )
+            .clicked()
+        {
+            let quantity = Quantity::new(self.amount, Unit::parse(&self.unit));
+            meal_planner.add_to_pantry(PantryItem::new(food, quantity, self.expires));
+            self.food.clear();
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        ui.group(|ui| self.add_ui(ui, meal_planner));
+        ui.add_space(DEFAULT_PADDING);
+
+        if meal_planner.get_pantry().is_empty() {
+            ui.label(hs(
+                "The pantry is empty. Add what you have at home, or mark shopping list items as bought.",
+            ));
+            return;
+        }
+
+        let today = Local::now().date_naive();
+        let mut remove = None;
+        Grid::new("pantry_items")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(hb("Ingredient"));
+                ui.label(hb("Quantity"));
+                ui.label(hb("Expires"));
+                ui.end_row();
+                for item in meal_planner.get_pantry_mut() {
+                    ui.label(&item.food);
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            DragValue::new(&mut item.quantity.amount)
+                                .range(0.0..=100000.)
+                                .speed(0.5),
+                        );
+                        ui.label(item.quantity.unit.symbol(item.quantity.amount));
+                    });
+                    expiry_ui(ui, item.expires, today);
+                    if ui
+                        .button(ICON_TRASH_2)
+                        .on_hover_text("Remove from pantry")
+                        .clicked()
+                    {
+                        remove = Some(item.id);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(id) = remove {
+            meal_planner.remove_from_pantry(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_food_under_other_names_is_topped_up() {
+        let mut meal_planner = MealPlanner::default();
+        meal_planner.add_to_pantry(PantryItem::new(" Tomatoes ", Quantity::grams(200.), None));
+        meal_planner.add_to_pantry(PantryItem::new(
+            "tomato",
+            Quantity::new(0.3, Unit::Kilogram),
+            None,
+        ));
+        meal_planner.add_to_pantry(PantryItem::new("scallions", Quantity::grams(50.), None));
+        meal_planner.add_to_pantry(PantryItem::new("green onion", Quantity::grams(25.), None));
+
+        let pantry = meal_planner.get_pantry();
+        assert_eq!(pantry.len(), 2);
+        assert_eq!(pantry[0].food, "Tomatoes");
+        assert_eq!(pantry[0].quantity, Quantity::grams(500.));
+        assert_eq!(pantry[1].quantity, Quantity::grams(75.));
+    }
+
+    #[test]
+    fn units_that_dont_convert_are_kept_apart() {
+        let mut meal_planner = MealPlanner::default();
+        let eggs = Quantity::new(6., Unit::parse(""));
+        meal_planner.add_to_pantry(PantryItem::new("eggs", eggs.clone(), None));
+        meal_planner.add_to_pantry(PantryItem::new("egg", Quantity::grams(100.), None));
+        meal_planner.add_to_pantry(PantryItem::new("eggplant", eggs.clone(), None));
+
+        let pantry = meal_planner.get_pantry();
+        assert_eq!(pantry.len(), 3);
+        assert_eq!(pantry[0].quantity, eggs);
+        assert!(pantry[1].is_food("Eggs", meal_planner.get_food_names()));
+        assert!(!pantry[2].is_food("egg", meal_planner.get_food_names()));
+    }
+}
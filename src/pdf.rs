@@ -0,0 +1,256 @@
+//! A small PDF writer, just enough for text and lines on a few pages. Uses the
+//! standard Helvetica fonts every PDF reader has, so nothing needs to be embedded.
+
+use std::io::Write;
+
+pub const A4_PORTRAIT: (f32, f32) = (595., 842.);
+pub const A4_LANDSCAPE: (f32, f32) = (842., 595.);
+
+/// Average Helvetica glyph width as a fraction of the font size. Good enough to
+/// wrap text without the full font metrics.
+const AVERAGE_CHAR_WIDTH: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(&self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// Maps a character to WinAnsiEncoding, the encoding of the standard fonts.
+fn win_ansi(c: char) -> u8 {
+    match c {
+        '€' => 0x80,
+        '…' => 0x85,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '\u{20}'..='\u{7e}' | '\u{a0}'..='\u{ff}' => c as u8,
+        _ => b'?',
+    }
+}
+
+/// Splits `text` into lines no wider than `width` points, breaking between words
+/// where possible.
+pub fn wrap(text: &str, size: f32, width: f32) -> Vec<String> {
+    let max_chars = ((width / (size * AVERAGE_CHAR_WIDTH)) as usize).max(1);
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word = word.to_string();
+            // Words longer than a whole line are cut.
+            while word.chars().count() > max_chars {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                let rest = word.split_off(word.char_indices().nth(max_chars).unwrap().0);
+                lines.push(word);
+                word = rest;
+            }
+            let needed = line.chars().count() + word.chars().count() + !line.is_empty() as usize;
+            if needed > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// One page being drawn. Coordinates start at the top left corner, in points.
+pub struct Page {
+    size: (f32, f32),
+    content: Vec<u8>,
+}
+
+impl Page {
+    pub fn width(&self) -> f32 {
+        self.size.0
+    }
+
+    pub fn height(&self) -> f32 {
+        self.size.1
+    }
+
+    /// Writes `text` with its baseline at `y`.
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: Font, text: &str) {
+        let mut escaped = vec![];
+        for byte in text.chars().map(win_ansi) {
+            if matches!(byte, b'(' | b')' | b'\\') {
+                escaped.push(b'\\');
+            }
+            escaped.push(byte);
+        }
+        let _ = write!(
+            self.content,
+            "BT /{} {} Tf {:.2} {:.2} Td (",
+            font.resource(),
+            size,
+            x,
+            self.size.1 - y
+        );
+        self.content.extend(escaped);
+        self.content.extend(b") Tj ET\n");
+    }
+
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32) {
+        let _ = writeln!(
+            self.content,
+            "{} w {:.2} {:.2} m {:.2} {:.2} l S",
+            width,
+            from.0,
+            self.size.1 - from.1,
+            to.0,
+            self.size.1 - to.1
+        );
+    }
+
+    /// Fills a rectangle with a shade of grey, 0 being black and 1 white.
+    pub fn fill_rect(&mut self, (x, y): (f32, f32), (width, height): (f32, f32), grey: f32) {
+        let _ = writeln!(
+            self.content,
+            "q {:.2} g {:.2} {:.2} {:.2} {:.2} re f Q",
+            grey,
+            x,
+            self.size.1 - y - height,
+            width,
+            height
+        );
+    }
+}
+
+#[derive(Default)]
+pub struct Document {
+    pages: Vec<Page>,
+}
+
+impl Document {
+    pub fn add_page(&mut self, size: (f32, f32)) -> &mut Page {
+        self.pages.push(Page {
+            size,
+            content: vec![],
+        });
+        self.pages.last_mut().unwrap()
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = vec![];
+        let page_count = self.pages.len();
+        // Objects 1 to 4 are the catalog, the page tree and the two fonts, then every
+        // page takes two objects: the page and its content.
+        let page_ids: Vec<usize> = (0..page_count).map(|idx| 5 + idx * 2).collect();
+        let kids = page_ids
+            .iter()
+            .map(|id| format!("{} 0 R", id))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids, page_count).into_bytes(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        ];
+        for (page, id) in self.pages.into_iter().zip(page_ids) {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    page.size.0,
+                    page.size.1,
+                    id + 1
+                )
+                .into_bytes(),
+            );
+            let mut stream = format!("<< /Length {} >>\nstream\n", page.content.len()).into_bytes();
+            stream.extend(page.content);
+            stream.extend(b"\nendstream");
+            objects.push(stream);
+        }
+
+        for (idx, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            let _ = writeln!(pdf, "{} 0 obj", idx + 1);
+            pdf.extend(object);
+            pdf.extend(b"\nendobj\n");
+        }
+
+        let xref_offset = pdf.len();
+        let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            pdf,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        );
+        pdf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Width of `chars` average characters at size 10.
+    fn width(chars: usize) -> f32 {
+        chars as f32 * 10. * AVERAGE_CHAR_WIDTH
+    }
+
+    #[test]
+    fn wrap_breaks_between_words() {
+        assert_eq!(
+            wrap("the quick brown fox jumps", 10., width(10)),
+            vec!["the quick", "brown fox", "jumps"]
+        );
+        assert_eq!(wrap("exactly 10", 10., width(10)), vec!["exactly 10"]);
+    }
+
+    #[test]
+    fn wrap_cuts_words_longer_than_a_line() {
+        assert_eq!(
+            wrap("a abcdefghijklmnop", 10., width(6)),
+            vec!["a", "abcdef", "ghijkl", "mnop"]
+        );
+        assert_eq!(wrap("ééééééé", 10., width(3)), vec!["ééé", "ééé", "é"]);
+    }
+
+    #[test]
+    fn wrap_keeps_paragraphs() {
+        assert_eq!(wrap("one\n\ntwo", 10., width(10)), vec!["one", "", "two"]);
+        assert_eq!(wrap("", 10., width(10)), Vec::<String>::new());
+        assert_eq!(wrap("tiny", 10., 0.), vec!["t", "i", "n", "y"]);
+    }
+
+    #[test]
+    fn text_is_escaped_and_encoded() {
+        let mut document = Document::default();
+        let page = document.add_page(A4_PORTRAIT);
+        page.text(0., 0., 10., Font::Regular, "(5 €) \\ ✓");
+        let content = &document.pages[0].content;
+        let start = content.iter().position(|b| *b == b'(').unwrap();
+        assert_eq!(&content[start..start + 14], b"(\\(5 \x80\\) \\\\ ?)");
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{Macros, Recipe};
+
+pub const DEFAULT_MEAL_SLOTS: [&str; 4] = ["Breakfast", "Lunch", "Dinner", "Snacks"];
+
+pub fn default_meal_slots() -> Vec<String> {
+    DEFAULT_MEAL_SLOTS.iter().map(|s| s.to_string()).collect()
+}
+
+/// How much of a recipe a plan entry stands for.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Portion {
+    /// A multiple of one recipe serving.
+    Servings(f32),
+    /// A weight in grams of the cooked recipe.
+    Grams(f32),
+}
+
+impl Default for Portion {
+    fn default() -> Self {
+        Portion::Servings(1.0)
+    }
+}
+
+impl Portion {
+    /// Number of recipe servings this portion amounts to.
+    pub fn servings(&self, recipe: &Recipe) -> f32 {
+        match self {
+            Portion::Servings(servings) => *servings,
+            Portion::Grams(grams) => {
+                let serving_weight = recipe.serving_weight();
+                if serving_weight > 0. {
+                    grams / serving_weight
+                } else {
+                    0.
+                }
+            }
+        }
+    }
+
+    pub fn is_single_serving(&self) -> bool {
+        *self == Portion::Servings(1.0)
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Portion::Servings(servings) if *servings == 1. => "1 serving".to_string(),
+            Portion::Servings(servings) => format!("{} servings", servings),
+            Portion::Grams(grams) => format!("{}g", grams),
+        }
+    }
+}
+
+/// Someone of the household the plan is made for.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Member {
+    pub id: Uuid,
+    pub name: String,
+    pub targets: Macros,
+}
+
+/// The portion one household member eats of a plan entry.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Share {
+    pub member: Uuid,
+    pub portion: Portion,
+}
+
+/// A recipe placed in the plan, together with the portion eaten.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "PlanEntryRepr")]
+pub struct PlanEntry {
+    pub recipe_id: Uuid,
+    /// Portion of every household member, unless `shares` says otherwise.
+    pub portion: Portion,
+    /// Who eats the entry and how much. Empty when the whole household eats `portion`.
+    pub shares: Vec<Share>,
+    /// The cooking event this entry eats from. Entries without one are cooked
+    /// on their own.
+    pub batch: Option<Uuid>,
+}
+
+/// Older states stored plan entries as bare recipe ids.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PlanEntryRepr {
+    Entry {
+        recipe_id: Uuid,
+        #[serde(default)]
+        portion: Portion,
+        #[serde(default)]
+        shares: Vec<Share>,
+        #[serde(default)]
+        batch: Option<Uuid>,
+    },
+    RecipeId(Uuid),
+}
+
+impl From<PlanEntryRepr> for PlanEntry {
+    fn from(repr: PlanEntryRepr) -> Self {
+        match repr {
+            PlanEntryRepr::Entry {
+                recipe_id,
+                portion,
+                shares,
+                batch,
+            } => Self {
+                recipe_id,
+                portion,
+                shares,
+                batch,
+            },
+            PlanEntryRepr::RecipeId(recipe_id) => Self::new(recipe_id),
+        }
+    }
+}
+
+impl PlanEntry {
+    pub fn new(recipe_id: Uuid) -> Self {
+        Self {
+            recipe_id,
+            portion: Portion::default(),
+            shares: vec![],
+            batch: None,
+        }
+    }
+}
+
+/// A recipe cooked once in a bigger quantity and eaten over several plan entries.
+/// It is cooked for the first entry linked to it, the later ones eat leftovers.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CookingEvent {
+    pub recipe_id: Uuid,
+    /// Number of recipe servings cooked.
+    pub servings: f32,
+}
+
+/// One day of the meal plan. Every slot holds the entries planned for that meal,
+/// in the same order as the configured meal slot names.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "DayPlanRepr")]
+pub struct DayPlan {
+    pub slots: Vec<Vec<PlanEntry>>,
+}
+
+/// Older states stored a day as a flat list of recipe ids.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DayPlanRepr {
+    Slotted { slots: Vec<Vec<PlanEntry>> },
+    Flat(Vec<PlanEntry>),
+}
+
+impl From<DayPlanRepr> for DayPlan {
+    fn from(repr: DayPlanRepr) -> Self {
+        match repr {
+            DayPlanRepr::Slotted { slots } => Self { slots },
+            DayPlanRepr::Flat(entries) => Self {
+                slots: vec![entries],
+            },
+        }
+    }
+}
+
+impl DayPlan {
+    pub fn with_slots(slot_count: usize) -> Self {
+        Self {
+            slots: vec![vec![]; slot_count],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(|slot| slot.is_empty())
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| slot.clear());
+    }
+
+    /// All entries of the day, slot by slot.
+    pub fn entries(&self) -> impl Iterator<Item = &PlanEntry> {
+        self.slots.iter().flatten()
+    }
+
+    /// Makes sure the day has exactly `slot_count` slots. Recipes from slots that
+    /// no longer exist are moved to the last remaining slot so nothing is lost.
+    pub fn resize_slots(&mut self, slot_count: usize) {
+        let slot_count = slot_count.max(1);
+        if self.slots.len() > slot_count {
+            let overflow: Vec<PlanEntry> = self.slots.drain(slot_count..).flatten().collect();
+            self.slots[slot_count - 1].extend(overflow);
+        } else {
+            self.slots.resize(slot_count, vec![]);
+        }
+    }
+
+    /// Removes the slot at `slot_idx`, merging its recipes into the neighbouring slot.
+    pub fn remove_slot(&mut self, slot_idx: usize) {
+        if self.slots.len() < 2 || slot_idx >= self.slots.len() {
+            return;
+        }
+        let removed = self.slots.remove(slot_idx);
+        let target = slot_idx.saturating_sub(1);
+        self.slots[target].extend(removed);
+    }
+}
+
+/// A named set of days saved from the plan, applied again later over any range of days.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlanTemplate {
+    pub name: String,
+    pub days: Vec<DayPlan>,
+    /// Cooking events the template entries are linked to.
+    #[serde(default)]
+    pub batches: HashMap<Uuid, CookingEvent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry() -> PlanEntry {
+        PlanEntry::new(Uuid::new_v4())
+    }
+
+    #[test]
+    fn bare_recipe_id_loads_as_single_serving() {
+        let id = Uuid::new_v4();
+        let entry: PlanEntry = serde_json::from_str(&format!("\"{}\"", id)).unwrap();
+        assert_eq!(entry, PlanEntry::new(id));
+        assert!(entry.portion.is_single_serving());
+    }
+
+    #[test]
+    fn entry_without_portion_defaults_to_one_serving() {
+        let id = Uuid::new_v4();
+        let entry: PlanEntry =
+            serde_json::from_str(&format!(r#"{{"recipe_id": "{}"}}"#, id)).unwrap();
+        assert_eq!(entry.portion, Portion::Servings(1.0));
+        assert!(entry.shares.is_empty());
+        assert_eq!(entry.batch, None);
+    }
+
+    #[test]
+    fn grams_portion_scales_by_serving_weight() {
+        let mut recipe = Recipe {
+            servings: 4,
+            ..Default::default()
+        };
+        recipe.macros.totalWeight = 800.;
+        assert_eq!(Portion::Grams(300.).servings(&recipe), 1.5);
+        recipe.macros.totalWeight = 0.;
+        assert_eq!(Portion::Grams(300.).servings(&recipe), 0.);
+    }
+
+    #[test]
+    fn resize_slots_moves_overflow_to_last_slot() {
+        let (a, b, c) = (entry(), entry(), entry());
+        let mut day = DayPlan {
+            slots: vec![vec![a.clone()], vec![b.clone()], vec![c.clone()]],
+        };
+        day.resize_slots(2);
+        assert_eq!(day.slots, vec![vec![a], vec![b, c]]);
+    }
+
+    #[test]
+    fn resize_slots_keeps_at_least_one_slot() {
+        let a = entry();
+        let mut day = DayPlan {
+            slots: vec![vec![], vec![a.clone()]],
+        };
+        day.resize_slots(0);
+        assert_eq!(day.slots, vec![vec![a]]);
+        day.resize_slots(3);
+        assert_eq!(day.slots.len(), 3);
+    }
+
+    #[test]
+    fn remove_slot_merges_into_previous_slot() {
+        let (a, b, c) = (entry(), entry(), entry());
+        let mut day = DayPlan {
+            slots: vec![vec![a.clone()], vec![b.clone()], vec![c.clone()]],
+        };
+        day.remove_slot(1);
+        assert_eq!(day.slots, vec![vec![a.clone(), b.clone()], vec![c.clone()]]);
+        day.remove_slot(0);
+        assert_eq!(day.slots, vec![vec![c.clone(), a, b]]);
+    }
+
+    #[test]
+    fn remove_slot_keeps_the_last_slot() {
+        let a = entry();
+        let mut day = DayPlan {
+            slots: vec![vec![a.clone()]],
+        };
+        day.remove_slot(0);
+        day.remove_slot(5);
+        assert_eq!(day.slots, vec![vec![a]]);
+    }
+
+    #[test]
+    fn flat_day_loads_into_first_slot() {
+        let id = Uuid::new_v4();
+        let day: DayPlan = serde_json::from_str(&format!("[\"{}\"]", id)).unwrap();
+        assert_eq!(day.slots, vec![vec![PlanEntry::new(id)]]);
+    }
+
+    #[test]
+    fn slotted_day_round_trips() {
+        let day = DayPlan {
+            slots: vec![vec![entry()], vec![]],
+        };
+        let json = serde_json::to_string(&day).unwrap();
+        assert_eq!(serde_json::from_str::<DayPlan>(&json).unwrap(), day);
+    }
+}
@@ -0,0 +1,612 @@
+use std::collections::{HashMap, HashSet};
+
+use egui::{CollapsingHeader, DragValue, Grid, Layout, ScrollArea};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    meal_planner::MealPlanner,
+    models::{Macros, Recipe},
+    plan::{DayPlan, PlanEntry, Portion},
+    typography::icons::{ICON_CHECK, ICON_SHUFFLE, ICON_TRASH_2, ICON_WAND_SPARKLES},
+    util::{hb, hs, DEFAULT_PADDING},
+};
+
+const PORTION_STEPS: [f32; 6] = [0.5, 0.75, 1., 1.25, 1.5, 2.];
+const IMPROVEMENT_PASSES: usize = 3;
+const MEAL_TYPE_PENALTY: f32 = 0.3;
+const REPEAT_PENALTY: f32 = 0.2;
+const JITTER: f32 = 0.05;
+
+/// Options for [`generate_plan`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneratorSettings {
+    /// Let the generator pick portion sizes other than one serving.
+    pub vary_portions: bool,
+    /// How often the same recipe may appear in the whole plan.
+    pub max_repeats: usize,
+    pub excluded_recipes: HashSet<Uuid>,
+    /// Recipes carrying any of these Edamam cautions are never picked.
+    pub excluded_cautions: HashSet<String>,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            vary_portions: true,
+            max_repeats: 2,
+            excluded_recipes: HashSet::new(),
+            excluded_cautions: HashSet::new(),
+        }
+    }
+}
+
+/// A proposed plan. `filled` lists the (day, slot) pairs the generator filled in.
+#[derive(Clone, Debug)]
+pub struct GeneratedPlan {
+    pub days: Vec<DayPlan>,
+    pub filled: HashSet<(usize, usize)>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Candidate {
+    recipe_id: Uuid,
+    servings: f32,
+    macros: Macros,
+}
+
+/// Small deterministic generator, so that "Regenerate" can explore other plans.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next_f32(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Weighted squared relative deviation of `total` from `targets`.
+/// Targets set to zero are ignored.
+pub fn deviation(total: &Macros, targets: &Macros) -> f32 {
+    let term = |value: f32, target: f32, weight: f32| {
+        if target > 0. {
+            weight * ((value - target) / target).powi(2)
+        } else {
+            0.
+        }
+    };
+    term(total.calories, targets.calories, 2.)
+        + term(total.protein, targets.protein, 1.5)
+        + term(total.carbs, targets.carbs, 1.)
+        + term(total.fat, targets.fat, 1.)
+}
+
+/// Whether a recipe's Edamam meal type fits a meal slot. Recipes without a meal
+/// type fit everywhere.
+fn fits_slot(recipe: &Recipe, slot_name: &str) -> bool {
+    let slot_name = slot_name.to_lowercase();
+    recipe.macros.mealType.is_empty()
+        || recipe.macros.mealType.iter().any(|meal_type| {
+            meal_type
+                .to_lowercase()
+                .split('/')
+                .any(|meal_type| slot_name.contains(meal_type.trim()))
+        })
+}
+
+/// Calories and macros of one portion of every entry of a day, which is what the
+/// targets are for.
+fn portion_macros(meal_planner: &MealPlanner, day: &DayPlan) -> Macros {
+    let mut total = Macros::default();
+    for entry in day.entries() {
+        if let Some(recipe) = meal_planner.get_recipe_by_id(&entry.recipe_id) {
+            total += recipe
+                .macros_per_serving()
+                .scaled(entry.portion.servings(recipe));
+        }
+    }
+    total
+}
+
+/// Fills the empty meal slots of the current plan with recipes from the recipe
+/// book, so that every day gets as close as possible to the calorie and macro
+/// targets. Slots that already hold recipes are left untouched and count towards
+/// their day's totals. Nothing is filled in without any target to aim for.
+pub fn generate_plan(
+    meal_planner: &MealPlanner,
+    settings: &GeneratorSettings,
+    seed: u64,
+) -> GeneratedPlan {
+    let targets = meal_planner.targets;
+    let slot_names = meal_planner.get_meal_slots();
+    let mut days = meal_planner.get_daily_plan().clone();
+    let mut filled = HashSet::new();
+    let has_targets = [
+        targets.calories,
+        targets.protein,
+        targets.carbs,
+        targets.fat,
+    ]
+    .iter()
+    .any(|target| *target > 0.);
+    if !has_targets {
+        return GeneratedPlan { days, filled };
+    }
+
+    let mut recipes: Vec<&Recipe> = meal_planner
+        .get_recipes()
+        .into_iter()
+        .filter(|recipe| recipe.macros.calories > 0)
+        .filter(|recipe| !settings.excluded_recipes.contains(&recipe.id))
+        .filter(|recipe| {
+            !recipe
+                .macros
+                .cautions
+                .iter()
+                .any(|caution| settings.excluded_cautions.contains(caution))
+        })
+        .collect();
+    // The recipe book is a hash map, sort it so the same seed gives the same plan.
+    recipes.sort_by_key(|recipe| recipe.id);
+
+    let portions: &[f32] = if settings.vary_portions {
+        &PORTION_STEPS
+    } else {
+        &[1.]
+    };
+    let candidates: Vec<Candidate> = recipes
+        .iter()
+        .flat_map(|recipe| {
+            let per_serving = recipe.macros_per_serving();
+            portions.iter().map(move |servings| Candidate {
+                recipe_id: recipe.id,
+                servings: *servings,
+                macros: per_serving.scaled(*servings),
+            })
+        })
+        .collect();
+    let recipe_by_id: HashMap<Uuid, &Recipe> =
+        recipes.iter().map(|recipe| (recipe.id, *recipe)).collect();
+
+    let mut uses: HashMap<Uuid, usize> = HashMap::new();
+    for entry in days.iter().flat_map(|day| day.entries()) {
+        *uses.entry(entry.recipe_id).or_default() += 1;
+    }
+
+    let mut rng = SplitMix(seed);
+
+    for (day_idx, day) in days.iter_mut().enumerate() {
+        let empty_slots: Vec<usize> = (0..day.slots.len())
+            .filter(|slot_idx| day.slots[*slot_idx].is_empty())
+            .collect();
+        if empty_slots.is_empty() {
+            continue;
+        }
+
+        let fixed = portion_macros(meal_planner, day);
+        let planned_today: HashSet<Uuid> = day.entries().map(|entry| entry.recipe_id).collect();
+
+        // Penalty for picking `candidate` in `slot_idx`, or `None` if it breaks a constraint.
+        let penalty = |candidate: &Candidate,
+                       slot_idx: usize,
+                       picks: &[Option<Candidate>],
+                       uses: &HashMap<Uuid, usize>,
+                       rng: &mut SplitMix| {
+            let used = uses.get(&candidate.recipe_id).copied().unwrap_or_default();
+            let twice_today = planned_today.contains(&candidate.recipe_id)
+                || picks
+                    .iter()
+                    .flatten()
+                    .any(|pick| pick.recipe_id == candidate.recipe_id);
+            if used >= settings.max_repeats || twice_today {
+                return None;
+            }
+            let slot_name = slot_names.get(slot_idx).map(String::as_str).unwrap_or("");
+            let meal_type = if fits_slot(recipe_by_id[&candidate.recipe_id], slot_name) {
+                0.
+            } else {
+                MEAL_TYPE_PENALTY
+            };
+            Some(meal_type + REPEAT_PENALTY * used as f32 + JITTER * rng.next_f32())
+        };
+
+        // Greedy pass: give every empty slot an equal share of what is left of the targets.
+        let mut picks: Vec<Option<Candidate>> = vec![None; empty_slots.len()];
+        for (pick_idx, slot_idx) in empty_slots.iter().enumerate() {
+            let so_far = picks
+                .iter()
+                .flatten()
+                .fold(fixed, |total, pick| total + pick.macros);
+            let remaining = (empty_slots.len() - pick_idx) as f32;
+            let slot_target = Macros {
+                calories: (targets.calories - so_far.calories).max(0.) / remaining,
+                protein: (targets.protein - so_far.protein).max(0.) / remaining,
+                carbs: (targets.carbs - so_far.carbs).max(0.) / remaining,
+                fat: (targets.fat - so_far.fat).max(0.) / remaining,
+            };
+
+            let mut best: Option<(f32, Candidate)> = None;
+            for candidate in &candidates {
+                let Some(penalty) = penalty(candidate, *slot_idx, &picks, &uses, &mut rng) else {
+                    continue;
+                };
+                let cost = deviation(&candidate.macros, &slot_target) + penalty;
+                if best.is_none_or(|(best_cost, _)| cost < best_cost) {
+                    best = Some((cost, *candidate));
+                }
+            }
+            if let Some((_, candidate)) = best {
+                *uses.entry(candidate.recipe_id).or_default() += 1;
+                picks[pick_idx] = Some(candidate);
+            }
+        }
+
+        // Improvement passes: revisit every pick with the rest of the day fixed.
+        for _ in 0..IMPROVEMENT_PASSES {
+            for (pick_idx, slot_idx) in empty_slots.iter().enumerate() {
+                let Some(current) = picks[pick_idx].take() else {
+                    continue;
+                };
+                *uses.get_mut(&current.recipe_id).unwrap() -= 1;
+                let others = picks
+                    .iter()
+                    .flatten()
+                    .fold(fixed, |total, pick| total + pick.macros);
+
+                let mut best = (
+                    deviation(&(others + current.macros), &targets)
+                        + penalty(&current, *slot_idx, &picks, &uses, &mut rng).unwrap_or(0.),
+                    current,
+                );
+                for candidate in &candidates {
+                    let Some(penalty) = penalty(candidate, *slot_idx, &picks, &uses, &mut rng)
+                    else {
+                        continue;
+                    };
+                    let cost = deviation(&(others + candidate.macros), &targets) + penalty;
+                    if cost < best.0 {
+                        best = (cost, *candidate);
+                    }
+                }
+                *uses.entry(best.1.recipe_id).or_default() += 1;
+                picks[pick_idx] = Some(best.1);
+            }
+        }
+
+        for (pick, slot_idx) in picks.into_iter().zip(empty_slots) {
+            if let Some(candidate) = pick {
+                day.slots[slot_idx].push(PlanEntry {
+                    recipe_id: candidate.recipe_id,
+                    portion: Portion::Servings(candidate.servings),
+                    shares: vec![],
+                    batch: None,
+                });
+                filled.insert((day_idx, slot_idx));
+            }
+        }
+    }
+
+    GeneratedPlan { days, filled }
+}
+
+fn macros_label(macros: &Macros) -> String {
+    format!(
+        "{:.0} kcal · P {:.0}g · C {:.0}g · F {:.0}g",
+        macros.calories, macros.protein, macros.carbs, macros.fat
+    )
+}
+
+/// Window content for configuring targets, generating a plan and previewing it
+/// before its meals are put in the empty slots of the planner.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlanGenerator {
+    settings: GeneratorSettings,
+    #[serde(skip)]
+    seed: u64,
+    #[serde(skip)]
+    preview: Option<GeneratedPlan>,
+}
+
+impl PlanGenerator {
+    pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        ui.group(|ui| {
+            ui.label(hb("Daily targets"));
+            Grid::new("generator_targets")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    let targets = &mut meal_planner.targets;
+                    ui.label("Calories");
+                    ui.add(
+                        DragValue::new(&mut targets.calories)
+                            .range(0..=10000)
+                            .suffix(" kcal"),
+                    );
+                    ui.end_row();
+                    ui.label("Protein");
+                    ui.add(
+                        DragValue::new(&mut targets.protein)
+                            .range(0..=1000)
+                            .suffix("g"),
+                    );
+                    ui.end_row();
+                    ui.label("Carbohydrates");
+                    ui.add(
+                        DragValue::new(&mut targets.carbs)
+                            .range(0..=1000)
+                            .suffix("g"),
+                    );
+                    ui.end_row();
+                    ui.label("Fat");
+                    ui.add(DragValue::new(&mut targets.fat).range(0..=1000).suffix("g"));
+                    ui.end_row();
+                });
+        });
+
+        ui.group(|ui| {
+            ui.label(hb("Options"));
+            ui.checkbox(&mut self.settings.vary_portions, "Adjust portion sizes");
+            ui.horizontal(|ui| {
+                ui.label("Use a recipe at most");
+                ui.add(DragValue::new(&mut self.settings.max_repeats).range(1..=14));
+                ui.label("times");
+            });
+
+            let mut cautions: Vec<String> = meal_planner
+                .get_recipes()
+                .iter()
+                .flat_map(|recipe| recipe.macros.cautions.iter().cloned())
+                .collect::<HashSet<String>>()
+                .into_iter()
+                .collect();
+            cautions.sort();
+            if !cautions.is_empty() {
+                CollapsingHeader::new("Exclude cautions").show(ui, |ui| {
+                    for caution in cautions {
+                        let mut excluded = self.settings.excluded_cautions.contains(&caution);
+                        if ui.checkbox(&mut excluded, &caution).changed() {
+                            if excluded {
+                                self.settings.excluded_cautions.insert(caution);
+                            } else {
+                                self.settings.excluded_cautions.remove(&caution);
+                            }
+                        }
+                    }
+                });
+            }
+
+            CollapsingHeader::new("Exclude recipes").show(ui, |ui| {
+                let mut recipes = meal_planner.get_recipes();
+                recipes.sort_by(|a, b| a.title.cmp(&b.title));
+                for recipe in recipes {
+                    let mut excluded = self.settings.excluded_recipes.contains(&recipe.id);
+                    if ui.checkbox(&mut excluded, &recipe.title).changed() {
+                        if excluded {
+                            self.settings.excluded_recipes.insert(recipe.id);
+                        } else {
+                            self.settings.excluded_recipes.remove(&recipe.id);
+                        }
+                    }
+                }
+            });
+        });
+
+        ui.add_space(DEFAULT_PADDING);
+        ui.horizontal(|ui| {
+            let label = if self.preview.is_some() {
+                format!("{} Regenerate", ICON_SHUFFLE)
+            } else {
+                format!("{} Generate plan", ICON_WAND_SPARKLES)
+            };
+            if ui.button(label).clicked() {
+                if self.preview.is_some() {
+                    self.seed = self.seed.wrapping_add(1);
+                }
+                self.preview = Some(generate_plan(meal_planner, &self.settings, self.seed));
+            }
+        });
+
+        let Some(preview) = &self.preview else {
+            ui.label(hs(
+                "Empty meal slots are filled with recipes from your recipe book. Meals already in the planner are kept.",
+            ));
+            return;
+        };
+
+        ui.separator();
+        if preview.filled.is_empty() {
+            ui.label(
+                "There are no empty slots to fill, no analysed recipes to fill them with, or no daily targets.",
+            );
+        }
+
+        let mut apply = false;
+        let mut discard = false;
+        ui.horizontal(|ui| {
+            apply = ui
+                .add_enabled(
+                    !preview.filled.is_empty(),
+                    egui::Button::new(format!("{} Apply to planner", ICON_CHECK)),
+                )
+                .clicked();
+            discard = ui.button(format!("{} Discard", ICON_TRASH_2)).clicked();
+        });
+
+        ScrollArea::vertical().show(ui, |ui| {
+            for (day_idx, day) in preview.days.iter().enumerate() {
+                let totals = portion_macros(meal_planner, day);
+                ui.horizontal(|ui| {
+                    ui.label(hb(&format!("Day {}", day_idx + 1)));
+                    ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(hs(&macros_label(&totals)));
+                    });
+                });
+                Grid::new(("generator_preview", day_idx))
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (slot_idx, slot) in day.slots.iter().enumerate() {
+                            let slot_name = meal_planner
+                                .get_meal_slots()
+                                .get(slot_idx)
+                                .cloned()
+                                .unwrap_or_default();
+                            ui.label(hs(&slot_name));
+                            let meals = slot
+                                .iter()
+                                .filter_map(|entry| {
+                                    let recipe = meal_planner.get_recipe_by_id(&entry.recipe_id)?;
+                                    Some(format!("{} ({})", recipe.title, entry.portion.label()))
+                                })
+                                .collect::<Vec<String>>()
+                                .join(", ");
+                            if preview.filled.contains(&(day_idx, slot_idx)) {
+                                ui.label(hb(&meals).strong());
+                            } else {
+                                ui.label(meals);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(DEFAULT_PADDING);
+            }
+            ui.label(hs(&format!(
+                "Target: {}",
+                macros_label(&meal_planner.targets)
+            )));
+        });
+
+        if apply {
+            let preview = self.preview.take().unwrap();
+            let meals = preview
+                .filled
+                .iter()
+                .filter_map(|&(day, slot)| {
+                    Some(((day, slot), preview.days.get(day)?.slots.get(slot)?.clone()))
+                })
+                .collect();
+            meal_planner.fill_empty_slots(meals);
+        }
+        if discard {
+            self.preview = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_recipe(meal_planner: &mut MealPlanner, calories: i32) -> Uuid {
+        let recipe = meal_planner.create_draft_recipe().unwrap();
+        recipe.title = format!("{} kcal", calories);
+        recipe.macros.calories = calories;
+        let id = recipe.id;
+        meal_planner.delete_draft_recipe();
+        id
+    }
+
+    fn calorie_planner() -> MealPlanner {
+        let mut meal_planner = MealPlanner::default();
+        meal_planner.targets = Macros {
+            calories: 2000.,
+            ..Default::default()
+        };
+        for calories in [150, 300, 450, 600, 800, 1100] {
+            add_recipe(&mut meal_planner, calories);
+        }
+        meal_planner
+    }
+
+    fn settings() -> GeneratorSettings {
+        GeneratorSettings {
+            vary_portions: false,
+            max_repeats: 10,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn deviation_ignores_zero_targets() {
+        let targets = Macros {
+            calories: 2000.,
+            ..Default::default()
+        };
+        let total = Macros {
+            calories: 1000.,
+            protein: 80.,
+            ..Default::default()
+        };
+        assert_eq!(deviation(&total, &targets), 0.5);
+        assert_eq!(deviation(&total, &Macros::default()), 0.);
+    }
+
+    #[test]
+    fn generate_plan_keeps_planned_meals() {
+        let mut meal_planner = calorie_planner();
+        let kept = meal_planner.get_recipes()[0].id;
+        meal_planner.add_recipe_to_planner(0, 1, 0, PlanEntry::new(kept));
+        let plan = generate_plan(&meal_planner, &settings(), 7);
+
+        assert!(!plan.filled.contains(&(0, 1)));
+        assert_eq!(plan.days[0].slots[1], vec![PlanEntry::new(kept)]);
+        for (day_idx, day) in plan.days.iter().enumerate() {
+            for (slot_idx, slot) in day.slots.iter().enumerate() {
+                assert_eq!(slot.len(), 1);
+                assert_eq!(
+                    plan.filled.contains(&(day_idx, slot_idx)),
+                    (day_idx, slot_idx) != (0, 1)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn generated_days_beat_random_picks() {
+        let meal_planner = calorie_planner();
+        let targets = meal_planner.targets;
+        let plan = generate_plan(&meal_planner, &settings(), 3);
+        let generated: f32 = plan
+            .days
+            .iter()
+            .map(|day| deviation(&portion_macros(&meal_planner, day), &targets))
+            .sum();
+
+        let mut recipes = meal_planner.get_recipes();
+        recipes.sort_by_key(|recipe| recipe.id);
+        let mut rng = SplitMix(3);
+        let random: f32 = plan
+            .days
+            .iter()
+            .map(|day| {
+                let total = day.slots.iter().fold(Macros::default(), |total, _| {
+                    let pick = (rng.next_f32() * recipes.len() as f32) as usize;
+                    total + recipes[pick.min(recipes.len() - 1)].macros_per_serving()
+                });
+                deviation(&total, &targets)
+            })
+            .sum();
+        assert!(generated < random, "{} >= {}", generated, random);
+        // The jitter lets a pick miss by about as much as it weighs.
+        assert!(generated / (plan.days.len() as f32) < JITTER);
+    }
+
+    #[test]
+    fn nothing_to_pick_or_aim_for_fills_nothing() {
+        let meal_planner = MealPlanner::default();
+        let plan = generate_plan(&meal_planner, &settings(), 0);
+        assert!(plan.filled.is_empty());
+        assert!(plan.days.iter().all(|day| day.is_empty()));
+
+        let mut meal_planner = calorie_planner();
+        meal_planner.targets = Macros::default();
+        let plan = generate_plan(&meal_planner, &settings(), 0);
+        assert!(plan.filled.is_empty());
+        assert!(plan.days.iter().all(|day| day.is_empty()));
+    }
+}
@@ -1,58 +1,141 @@
+use std::collections::{HashMap, HashSet};
+
 use egui::*;
 use uuid::Uuid;
 
 use crate::{
+    leftovers::{batch_usage, entry_status, uneaten_servings, BatchUsage, EntryBatchStatus},
     meal_planner::MealPlanner,
     models::{AnalysisResponseView, Recipe},
-    typography::icons::{ICON_CLIPBOARD_PASTE, ICON_MONITOR_COG, ICON_TRASH_2},
-    util::ls,
+    plan::{DayPlan, PlanEntry, Portion},
+    summary::{day_macros, target_text},
+    typography::icons::{
+        ICON_CHART_PIE, ICON_CLIPBOARD_PASTE, ICON_COOKING_POT, ICON_COPY, ICON_COPY_PLUS,
+        ICON_LINK, ICON_MONITOR_COG, ICON_MOVE, ICON_REFRIGERATOR, ICON_TRASH_2,
+        ICON_TRIANGLE_ALERT, ICON_UNLINK,
+    },
+    util::{hb, hs, ls},
 };
 
-/// What is being dragged.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+/// A plan entry: its day, slot and position, and the recipe it was showing.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize,
+)]
 pub struct Location {
     pub col: usize,
+    pub slot: usize,
     pub row: usize,
     pub recipe_id: Uuid,
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+/// What is being dragged onto the planner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DragPayload {
+    /// Recipes from the gallery.
+    Gallery(Vec<Uuid>),
+    /// Entries already in the plan.
+    Plan(Vec<Location>),
+}
+
+impl DragPayload {
+    pub fn len(&self) -> usize {
+        match self {
+            DragPayload::Gallery(recipes) => recipes.len(),
+            DragPayload::Plan(entries) => entries.len(),
+        }
+    }
+
+    fn contains(&self, location: &Location) -> bool {
+        matches!(self, DragPayload::Plan(entries) if entries.contains(location))
+    }
+}
+
+/// Where a payload is dropped: day, slot and position in the slot.
+type DropTarget = (usize, usize, usize);
+
+/// Shows how many items are dragged when there's more than one.
+pub fn drag_count_ui(ui: &Ui) {
+    let Some(payload) = DragAndDrop::payload::<DragPayload>(ui.ctx()) else {
+        return;
+    };
+    if payload.len() > 1 {
+        // Recipes dragged from the gallery are always copied.
+        let hint = match *payload {
+            DragPayload::Plan(_) => " · hold Ctrl to copy",
+            DragPayload::Gallery(_) => "",
+        };
+        show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), Id::new("drag_count"), |ui| {
+            ui.label(format!("{} recipes{}", payload.len(), hint));
+        });
+    }
+}
+
+/// Plan edits triggered from the keyboard on a focused entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum KeyboardAction {
+    Up,
+    Down,
+    PreviousDay,
+    NextDay,
+    CopyToPreviousDay,
+    CopyToNextDay,
+    Duplicate,
+    Remove,
+    OpenMenu,
+}
+
+const KEYBOARD_HINT: &str = "Arrows: move · Ctrl+←/→: copy to another day · Ctrl+D: duplicate · Delete: remove · Enter: menu · Ctrl/Shift-click: select several, drag with Ctrl to copy";
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct Planner {
     search_term: String,
     collapsible_nutrients: Vec<AnalysisResponseView>,
     context_menu_pos: Pos2,
     show_context_menu: bool,
     context_menu_payload: Option<Location>,
-}
-
-impl Default for Planner {
-    fn default() -> Self {
-        Self {
-            search_term: String::new(),
-            collapsible_nutrients: (0..7).map(|_| AnalysisResponseView::default()).collect(),
-            context_menu_pos: Pos2::default(),
-            show_context_menu: false,
-            context_menu_payload: None,
-        }
-    }
+    nutrition_entry: Option<Location>,
+    #[serde(skip)]
+    keyboard_action: Option<(Location, KeyboardAction)>,
+    /// Entry (day, slot, row) that should take keyboard focus on the next frame.
+    #[serde(skip)]
+    pending_focus: Option<(usize, usize, usize)>,
+    /// Entries selected with Ctrl/Shift-click, dragged together.
+    #[serde(skip)]
+    selection: Vec<Location>,
 }
 
 impl Planner {
-    pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
-        // If there is a drop, store the location of the item being dragged, and the destination for the drop.
-        let mut from = None;
-        let mut to = None;
+    /// Draws the plan. Returns the id of a recipe the user asked to edit.
+    pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) -> Option<Uuid> {
+        // If there is a drop, store what was dragged, and the destination for the drop.
+        let mut dropped = None;
+        drag_count_ui(ui);
+        self.selection.retain(|location| {
+            meal_planner
+                .get_plan_entry(location.col, location.slot, location.row)
+                .is_some_and(|entry| entry.recipe_id == location.recipe_id)
+        });
+        // One nutrients view per day, the plan length can change at any time.
+        self.collapsible_nutrients
+            .resize(meal_planner.get_daily_plan().len(), AnalysisResponseView);
+        let batches = batch_usage(meal_planner.get_daily_plan(), meal_planner);
         ui.columns(meal_planner.get_daily_plan().len(), |uis| {
             for (col_idx, column) in meal_planner.get_daily_plan().clone().iter().enumerate() {
                 let ui = &mut uis[col_idx];
                 ui.horizontal(|ui| {
                     ui.heading(format!("Day {}", col_idx + 1));
+                    let date = meal_planner.plan_day_date(col_idx);
+                    ui.label(hs(&date.format("%a %-d %b").to_string()));
                     ui.with_layout(Layout::right_to_left(egui::Align::Min), |ui| {
                         let clear_btn = Button::new(ICON_TRASH_2);
                         let tooltip_ui = |ui: &mut Ui| {
                             ui.label("Clear meals");
                         };
-                        if ui.add(clear_btn).on_hover_ui(tooltip_ui).clicked() {
+                        if ui
+                            .add_enabled(!column.is_empty(), clear_btn)
+                            .on_hover_ui(tooltip_ui)
+                            .clicked()
+                        {
                             meal_planner.clear_planner_day(col_idx);
                         };
 
@@ -71,137 +154,30 @@ impl Planner {
                     .id_salt(format!("scroll_area{}", col_idx))
                     .show(ui, |ui| {
                         let pointer_pos = ui.ctx().pointer_latest_pos();
-                        let pointer_any_pressed = ui.ctx().input(|i| i.pointer.any_pressed());
-                        let pointer_is_over_area = ui.ctx().is_pointer_over_area();
-
-                        let frame = Frame::default().inner_margin(4.0);
-
-                        let (_, dropped_payload) = ui.dnd_drop_zone::<Location, ()>(frame, |ui| {
-                            ui.set_min_size(vec2(ui.available_width(), 100.0));
-                            for (row_idx, recipe_id) in column.iter().enumerate() {
-                                let ui_item_id =
-                                    Id::new(("my_drag_and_drop_demo", col_idx, row_idx));
-                                let item_location = Location {
-                                    col: col_idx,
-                                    row: row_idx,
-                                    recipe_id: *recipe_id,
-                                };
-                                let response = ui
-                                    .dnd_drag_source(ui_item_id, item_location, |ui| {
-                                        Frame::default()
-                                            .show(ui, |ui| {
-                                                ui.label(
-                                                    ls(&meal_planner
-                                                        .get_recipe_by_id(recipe_id)
-                                                        .unwrap()
-                                                        .title)
-                                                    .size(16.),
-                                                );
-                                                ui.separator();
-                                                ui.interact(
-                                                    ui.max_rect(),
-                                                    ui_item_id,
-                                                    Sense::click_and_drag(),
-                                                )
-                                            })
-                                            .inner
-                                    })
-                                    .inner;
-
-                                if response.clicked_by(PointerButton::Secondary) {
-                                    if let Some(pos) = pointer_pos {
-                                        self.context_menu_payload = Some(Location {
-                                            col: col_idx,
-                                            row: row_idx,
-                                            recipe_id: *recipe_id,
-                                        });
-                                        self.context_menu_pos = pos;
-                                    }
-                                    self.show_context_menu = true;
-                                }
-                                // Detect drops onto this item:
-                                if let (Some(pointer), Some(hovered_payload)) = (
-                                    ui.input(|i| i.pointer.interact_pos()),
-                                    response.dnd_hover_payload::<Location>(),
-                                ) {
-                                    let rect = response.rect;
-
-                                    // Preview insertion:
-                                    let stroke = egui::Stroke::new(1.0, Color32::WHITE);
-                                    let insert_row_idx = if *hovered_payload == item_location {
-                                        // We are dragged onto ourselves
-                                        ui.painter().hline(rect.x_range(), rect.center().y, stroke);
-                                        row_idx
-                                    } else if pointer.y < rect.center().y {
-                                        // Above us
-                                        ui.painter().hline(rect.x_range(), rect.top(), stroke);
-                                        row_idx
-                                    } else {
-                                        // Below us
-                                        ui.painter().hline(rect.x_range(), rect.bottom(), stroke);
-                                        row_idx + 1
-                                    };
-
-                                    if let Some(dragged_payload) = response.dnd_release_payload() {
-                                        // The user dropped onto this item.
-                                        from = Some(dragged_payload);
-                                        to = Some(Location {
-                                            col: col_idx,
-                                            row: insert_row_idx,
-                                            recipe_id: *recipe_id,
-                                        });
-                                    }
-                                }
-                            }
-                        });
-
-                        if self.show_context_menu {
-                            // Draw an `Area` on top (foreground) at the stored position
-                            egui::Area::new("my_context_menu_area".into())
-                                .order(egui::Order::Foreground)
-                                .fixed_pos(self.context_menu_pos)
-                                .show(ui.ctx(), |ui| {
-                                    egui::Frame::popup(ui.style()).show(ui, |ui| {
-                                        if ui.button(format!("{} Edit", ICON_MONITOR_COG)).clicked()
-                                        {
-                                        }
-                                        if ui.button(format!("{} Remove", ICON_TRASH_2)).clicked() {
-                                            if let Some(payload) = self.context_menu_payload {
-                                                meal_planner.remove_planner_recipe(
-                                                    payload.col,
-                                                    payload.row,
-                                                );
-                                                self.context_menu_payload = None;
-                                                self.show_context_menu = false;
-                                            }
-                                        }
-                                    });
-                                });
-
-                            // --- 4) Close the menu if the user clicks elsewhere ---
-                            if pointer_any_pressed && !pointer_is_over_area {
-                                self.show_context_menu = false;
-                            }
-                        }
 
-                        if let Some(dragged_payload) = dropped_payload {
-                            // The user dropped onto the column, but not on any one item.
-                            let recipe_id = dragged_payload.recipe_id;
-                            from = Some(dragged_payload);
-                            to = Some(Location {
-                                col: col_idx,
-                                row: usize::MAX, // Inset last
-                                recipe_id,
-                            });
+                        for (slot_idx, slot) in column.slots.iter().enumerate() {
+                            if let Some(slot_drop) = self.slot_ui(
+                                ui,
+                                meal_planner,
+                                &batches,
+                                (col_idx, slot_idx),
+                                slot,
+                                pointer_pos,
+                            ) {
+                                dropped = Some(slot_drop);
+                            }
                         }
 
                         // footer
                         let mut total_daily = Recipe::default();
-                        for recipe_id in column {
-                            total_daily = meal_planner
-                                .get_recipe_by_id(recipe_id)
-                                .unwrap()
-                                .merge(&total_daily);
+                        for entry in column.entries() {
+                            let recipe = meal_planner.get_recipe_by_id(&entry.recipe_id).unwrap();
+                            let servings = meal_planner.entry_servings(entry, recipe);
+                            total_daily = recipe.merge_portion(servings, &total_daily);
+                        }
+
+                        if !meal_planner.get_members().is_empty() {
+                            members_day_ui(ui, meal_planner, col_idx, column);
                         }
 
                         self.collapsible_nutrients[col_idx].ui(
@@ -214,21 +190,966 @@ impl Planner {
             }
         });
 
-        if let (Some(from), Some(mut to)) = (from, to) {
-            if from.col == to.col {
-                // Dragging within the same column.
-                // Adjust row index if we are re-ordering:
-                to.row -= (from.row < to.row) as usize;
+        if let Some((location, action)) = self.keyboard_action.take() {
+            self.apply_keyboard_action(meal_planner, location, action);
+        }
+
+        let edit_recipe = self.context_menu_ui(ui.ctx(), meal_planner);
+        self.entry_nutrition_ui(ui.ctx(), meal_planner);
+
+        if let Some((payload, target)) = dropped {
+            let copy = ui.input(|i| i.modifiers.command);
+            self.apply_drop(meal_planner, payload, target, copy);
+        }
+
+        edit_recipe
+    }
+
+    /// Inserts dropped recipes or entries at `target`, in the order they were
+    /// selected. Plan entries are moved unless `copy` is set.
+    fn apply_drop(
+        &mut self,
+        meal_planner: &mut MealPlanner,
+        payload: DragPayload,
+        (to_col, to_slot, mut to_row): DropTarget,
+        copy: bool,
+    ) {
+        let entries: Vec<PlanEntry> = match payload {
+            DragPayload::Gallery(recipes) => recipes
+                .into_iter()
+                .filter(|id| meal_planner.get_recipe_by_id(id).is_some())
+                .map(PlanEntry::new)
+                .collect(),
+            DragPayload::Plan(mut locations) => {
+                // Leave out entries moved or removed since the drag started, and
+                // entries listed twice, so only what was dragged gets removed.
+                let mut seen = HashSet::new();
+                locations.retain(|location| {
+                    seen.insert(*location)
+                        && meal_planner
+                            .get_plan_entry(location.col, location.slot, location.row)
+                            .is_some_and(|entry| entry.recipe_id == location.recipe_id)
+                });
+                let entries = locations
+                    .iter()
+                    .filter_map(|location| {
+                        meal_planner
+                            .get_plan_entry(location.col, location.slot, location.row)
+                            .cloned()
+                    })
+                    .collect();
+                if !copy {
+                    // Remove from the back so the remaining positions stay valid.
+                    locations.sort_by_key(|l| std::cmp::Reverse((l.col, l.slot, l.row)));
+                    for location in locations {
+                        if location.col == to_col
+                            && location.slot == to_slot
+                            && location.row < to_row
+                        {
+                            to_row -= 1;
+                        }
+                        meal_planner.remove_planner_recipe(
+                            location.col,
+                            location.slot,
+                            location.row,
+                        );
+                    }
+                }
+                entries
+            }
+        };
+
+        self.selection.clear();
+        let mut row = to_row;
+        for entry in entries {
+            let recipe_id = entry.recipe_id;
+            let inserted = meal_planner.add_recipe_to_planner(to_col, to_slot, row, entry);
+            self.selection.push(Location {
+                col: to_col,
+                slot: to_slot,
+                row: inserted,
+                recipe_id,
+            });
+            row = inserted + 1;
+        }
+    }
+
+    /// Ctrl-click toggles an entry in the selection, Shift-click extends it over the
+    /// day up to the entry, a plain click selects just the entry.
+    fn select(&mut self, ui: &Ui, meal_planner: &MealPlanner, location: Location) {
+        let modifiers = ui.input(|i| i.modifiers);
+        if modifiers.command {
+            match self.selection.iter().position(|l| *l == location) {
+                Some(idx) => {
+                    self.selection.remove(idx);
+                }
+                None => self.selection.push(location),
             }
+            return;
+        }
+        let anchor = self.selection.last().copied();
+        match anchor {
+            Some(anchor) if modifiers.shift && anchor.col == location.col => {
+                let day = &meal_planner.get_daily_plan()[location.col];
+                let (start, end) = if (anchor.slot, anchor.row) <= (location.slot, location.row) {
+                    ((anchor.slot, anchor.row), (location.slot, location.row))
+                } else {
+                    ((location.slot, location.row), (anchor.slot, anchor.row))
+                };
+                for (slot_idx, slot) in day.slots.iter().enumerate() {
+                    for (row_idx, entry) in slot.iter().enumerate() {
+                        let in_range = (start..=end).contains(&(slot_idx, row_idx));
+                        let location = Location {
+                            col: location.col,
+                            slot: slot_idx,
+                            row: row_idx,
+                            recipe_id: entry.recipe_id,
+                        };
+                        if in_range && !self.selection.contains(&location) {
+                            self.selection.push(location);
+                        }
+                    }
+                }
+            }
+            _ => self.selection = vec![location],
+        }
+    }
+
+    fn apply_keyboard_action(
+        &mut self,
+        meal_planner: &mut MealPlanner,
+        location: Location,
+        action: KeyboardAction,
+    ) {
+        let Location { col, slot, row, .. } = location;
+        let plan = meal_planner.get_daily_plan();
+        let slot_len = |day: usize, slot: usize| plan[day].slots[slot].len();
+        let slot_count = meal_planner.get_meal_slots().len();
+        let plan_length = plan.len();
+
+        // Where the entry should end up when it moves: (day, slot, row).
+        let destination = match action {
+            KeyboardAction::Up if row > 0 => Some((col, slot, row - 1)),
+            KeyboardAction::Up if slot > 0 => Some((col, slot - 1, usize::MAX)),
+            KeyboardAction::Down if row + 1 < slot_len(col, slot) => Some((col, slot, row + 1)),
+            KeyboardAction::Down if slot + 1 < slot_count => Some((col, slot + 1, 0)),
+            KeyboardAction::PreviousDay if col > 0 => Some((col - 1, slot, row)),
+            KeyboardAction::NextDay if col + 1 < plan_length => Some((col + 1, slot, row)),
+            _ => None,
+        };
+
+        match action {
+            KeyboardAction::Up
+            | KeyboardAction::Down
+            | KeyboardAction::PreviousDay
+            | KeyboardAction::NextDay => {
+                if let Some((to_day, to_slot, to_row)) = destination {
+                    if let Some(entry) = meal_planner.remove_planner_recipe(col, slot, row) {
+                        let to_row =
+                            meal_planner.add_recipe_to_planner(to_day, to_slot, to_row, entry);
+                        self.pending_focus = Some((to_day, to_slot, to_row));
+                    }
+                }
+            }
+            KeyboardAction::CopyToPreviousDay if col > 0 => {
+                meal_planner.transfer_planner_recipe(col, slot, row, col - 1, true);
+            }
+            KeyboardAction::CopyToNextDay if col + 1 < plan_length => {
+                meal_planner.transfer_planner_recipe(col, slot, row, col + 1, true);
+            }
+            KeyboardAction::Duplicate => {
+                meal_planner.duplicate_planner_recipe(col, slot, row);
+                self.pending_focus = Some((col, slot, row + 1));
+            }
+            KeyboardAction::Remove => {
+                meal_planner.delete_planner_recipe(col, slot, row);
+                let remaining = meal_planner.get_daily_plan()[col].slots[slot].len();
+                if remaining > 0 {
+                    self.pending_focus = Some((col, slot, row.min(remaining - 1)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn close_context_menu(&mut self) {
+        self.context_menu_payload = None;
+        self.show_context_menu = false;
+    }
 
-            let recipe_id = if from.row == usize::MAX {
-                from.recipe_id
-            } else {
-                meal_planner.remove_planner_recipe(from.col, from.row)
+    /// Right-click menu of a plan entry. Returns the id of a recipe to open in the editor.
+    fn context_menu_ui(
+        &mut self,
+        ctx: &egui::Context,
+        meal_planner: &mut MealPlanner,
+    ) -> Option<Uuid> {
+        let mut edit_recipe = None;
+        let (Some(payload), true) = (self.context_menu_payload, self.show_context_menu) else {
+            return None;
+        };
+        let still_planned = meal_planner
+            .get_plan_entry(payload.col, payload.slot, payload.row)
+            .is_some_and(|entry| entry.recipe_id == payload.recipe_id);
+        if !still_planned {
+            // The entry was moved or removed while the menu was open.
+            self.close_context_menu();
+            return None;
+        }
+        let pointer_any_pressed = ctx.input(|i| i.pointer.any_pressed());
+        let pointer_is_over_area = ctx.is_pointer_over_area();
+        let plan_length = meal_planner.plan_length();
+
+        // Draw an `Area` on top (foreground) at the stored position
+        egui::Area::new("my_context_menu_area".into())
+            .order(egui::Order::Foreground)
+            .fixed_pos(self.context_menu_pos)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    if ui.button(format!("{} Edit", ICON_MONITOR_COG)).clicked() {
+                        edit_recipe = Some(payload.recipe_id);
+                        self.close_context_menu();
+                    }
+                    if ui.button(format!("{} Nutrition", ICON_CHART_PIE)).clicked() {
+                        self.nutrition_entry = Some(payload);
+                        self.close_context_menu();
+                    }
+                    if ui.button(format!("{} Duplicate", ICON_COPY)).clicked() {
+                        meal_planner.duplicate_planner_recipe(
+                            payload.col,
+                            payload.slot,
+                            payload.row,
+                        );
+                        self.close_context_menu();
+                    }
+                    ui.menu_button(format!("{} Move to", ICON_MOVE), |ui| {
+                        for day in (0..plan_length).filter(|day| *day != payload.col) {
+                            if ui.button(format!("Day {}", day + 1)).clicked() {
+                                meal_planner.transfer_planner_recipe(
+                                    payload.col,
+                                    payload.slot,
+                                    payload.row,
+                                    day,
+                                    false,
+                                );
+                                self.close_context_menu();
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button(format!("{} Copy to", ICON_COPY_PLUS), |ui| {
+                        for day in (0..plan_length).filter(|day| *day != payload.col) {
+                            if ui.button(format!("Day {}", day + 1)).clicked() {
+                                meal_planner.transfer_planner_recipe(
+                                    payload.col,
+                                    payload.slot,
+                                    payload.row,
+                                    day,
+                                    true,
+                                );
+                                self.close_context_menu();
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    if ui.button(format!("{} Remove", ICON_TRASH_2)).clicked() {
+                        meal_planner.delete_planner_recipe(payload.col, payload.slot, payload.row);
+                        self.close_context_menu();
+                    }
+                    if !self.show_context_menu {
+                        // The entry may have moved, so the rest of the menu would
+                        // act on whatever took its place.
+                        return;
+                    }
+
+                    ui.separator();
+                    self.batch_menu_ui(ui, meal_planner, payload);
+
+                    let serving_weight = meal_planner
+                        .get_recipe_by_id(&payload.recipe_id)
+                        .map(|recipe| recipe.serving_weight())
+                        .unwrap_or_default();
+                    if let Some(entry) =
+                        meal_planner.get_plan_entry_mut(payload.col, payload.slot, payload.row)
+                    {
+                        ui.separator();
+                        if entry.shares.is_empty() {
+                            portion_ui(ui, &mut entry.portion, serving_weight);
+                        } else {
+                            ui.label(hb("Portion"));
+                            if ui.button("Same portion for everyone").clicked() {
+                                entry.shares.clear();
+                            }
+                        }
+                    }
+                    shares_ui(ui, meal_planner, payload);
+                });
+            });
+
+        // Close the menu if the user clicks elsewhere
+        if pointer_any_pressed && !pointer_is_over_area {
+            self.show_context_menu = false;
+        }
+
+        edit_recipe
+    }
+
+    /// Context menu entries linking a plan entry to a cooking event.
+    fn batch_menu_ui(&mut self, ui: &mut Ui, meal_planner: &mut MealPlanner, payload: Location) {
+        let Location { col, slot, row, .. } = payload;
+        let Some(entry) = meal_planner.get_plan_entry(col, slot, row).cloned() else {
+            return;
+        };
+
+        if let Some(batch_id) = entry.batch {
+            let Some(mut servings) = meal_planner.get_batch(&batch_id).map(|b| b.servings) else {
+                return;
             };
+            ui.horizontal(|ui| {
+                ui.label("Batch servings");
+                let drag = DragValue::new(&mut servings).range(0.5..=100.).speed(0.5);
+                if ui.add(drag).changed() {
+                    meal_planner.set_batch_servings(&batch_id, servings);
+                }
+            });
+            if ui
+                .button(format!("{} Unlink from batch", ICON_UNLINK))
+                .clicked()
+            {
+                meal_planner.link_batch(col, slot, row, None);
+                self.close_context_menu();
+            }
+            return;
+        }
+
+        if ui
+            .button(format!("{} Cook as batch", ICON_COOKING_POT))
+            .on_hover_text("Cook the whole recipe for this meal and eat the leftovers later on")
+            .clicked()
+        {
+            meal_planner.start_batch(col, slot, row);
+            self.close_context_menu();
+        }
+
+        let mut cooked: Vec<(Uuid, (usize, usize, usize))> =
+            batch_usage(meal_planner.get_daily_plan(), meal_planner)
+                .into_iter()
+                .filter(|(id, _)| {
+                    meal_planner
+                        .get_batch(id)
+                        .is_some_and(|batch| batch.recipe_id == entry.recipe_id)
+                })
+                .filter_map(|(id, usage)| Some((id, usage.cook_location()?)))
+                .collect();
+        if cooked.is_empty() {
+            return;
+        }
+        cooked.sort_by_key(|(_, location)| *location);
+        ui.menu_button(format!("{} Eat leftovers from", ICON_LINK), |ui| {
+            for (batch_id, (day, slot_idx, _)) in cooked {
+                let slot_name = meal_planner
+                    .get_meal_slots()
+                    .get(slot_idx)
+                    .cloned()
+                    .unwrap_or_default();
+                if ui
+                    .button(format!("Day {}, {}", day + 1, slot_name))
+                    .clicked()
+                {
+                    meal_planner.link_batch(col, slot, row, Some(batch_id));
+                    self.close_context_menu();
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+
+    /// Nutrition facts of a single plan entry, scaled to what everyone eating it has.
+    fn entry_nutrition_ui(&mut self, ctx: &egui::Context, meal_planner: &MealPlanner) {
+        let Some(location) = self.nutrition_entry else {
+            return;
+        };
+        let entry = meal_planner
+            .get_plan_entry(location.col, location.slot, location.row)
+            .cloned()
+            .filter(|entry| entry.recipe_id == location.recipe_id);
+        let (Some(entry), Some(recipe)) =
+            (entry, meal_planner.get_recipe_by_id(&location.recipe_id))
+        else {
+            // The entry was moved or removed in the meantime.
+            self.nutrition_entry = None;
+            return;
+        };
+
+        let servings = meal_planner.entry_servings(&entry, recipe);
+        let portion_nutrients = recipe.merge_portion(servings, &Recipe::default());
+        let members = meal_planner.get_members();
+        let label = if members.is_empty() {
+            format!("Amount per portion ({})", entry.portion.label())
+        } else {
+            format!("Amount for everyone ({} servings)", servings_text(servings))
+        };
+
+        let mut open = true;
+        egui::Window::new(recipe.to_string())
+            .id(Id::new("planner_entry_nutrition"))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                for member in members {
+                    let member_servings = meal_planner.member_servings(&entry, &member.id, recipe);
+                    if member_servings > 0. {
+                        ui.label(hs(&format!(
+                            "{}: {} servings",
+                            member.name,
+                            servings_text(member_servings)
+                        )));
+                    }
+                }
+                AnalysisResponseView.ui(ui, &portion_nutrients.macros, 1, &label);
+            });
+        if !open {
+            self.nutrition_entry = None;
+        }
+    }
+
+    /// Draws one meal slot of a day as its own drop zone.
+    /// Returns the source and destination of a drop onto this slot, if any.
+    fn slot_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        meal_planner: &MealPlanner,
+        batches: &HashMap<Uuid, BatchUsage>,
+        (col_idx, slot_idx): (usize, usize),
+        slot: &[PlanEntry],
+        pointer_pos: Option<Pos2>,
+    ) -> Option<(DragPayload, DropTarget)> {
+        let mut dropped = None;
+        let slot_name = meal_planner
+            .get_meal_slots()
+            .get(slot_idx)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut slot_total = Recipe::default();
+        for entry in slot {
+            let recipe = meal_planner.get_recipe_by_id(&entry.recipe_id).unwrap();
+            let servings = meal_planner.entry_servings(entry, recipe);
+            slot_total = recipe.merge_portion(servings, &slot_total);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(hb(&slot_name));
+            ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(hs(&slot_total.macros_summary()));
+            });
+        });
+
+        let frame = Frame::default().inner_margin(4.0);
+
+        let (_, dropped_payload) = ui.dnd_drop_zone::<DragPayload, ()>(frame, |ui| {
+            ui.set_min_size(vec2(ui.available_width(), 40.0));
+            for (row_idx, entry) in slot.iter().enumerate() {
+                let recipe_id = &entry.recipe_id;
+                let ui_item_id = Id::new(("my_drag_and_drop_demo", col_idx, slot_idx, row_idx));
+                let item_location = Location {
+                    col: col_idx,
+                    slot: slot_idx,
+                    row: row_idx,
+                    recipe_id: *recipe_id,
+                };
+                let is_selected = self.selection.contains(&item_location);
+                let payload = if is_selected {
+                    DragPayload::Plan(self.selection.clone())
+                } else {
+                    DragPayload::Plan(vec![item_location])
+                };
+                let response = ui
+                    .dnd_drag_source(ui_item_id, payload, |ui| {
+                        Frame::default()
+                            .show(ui, |ui| {
+                                ui.label(
+                                    ls(&meal_planner.get_recipe_by_id(recipe_id).unwrap().title)
+                                        .size(16.),
+                                );
+                                if let Some(shares) = shares_text(meal_planner, entry) {
+                                    ui.label(hs(&shares));
+                                } else if !entry.portion.is_single_serving() {
+                                    ui.label(hs(&entry.portion.label()));
+                                }
+                                if let Some(usage) = entry.batch.and_then(|id| batches.get(&id)) {
+                                    batch_ui(ui, usage, (col_idx, slot_idx, row_idx));
+                                }
+                                ui.separator();
+                                ui.interact(ui.max_rect(), ui_item_id, Sense::click_and_drag())
+                            })
+                            .inner
+                    })
+                    .inner;
+
+                let recipe_title = meal_planner
+                    .get_recipe_by_id(recipe_id)
+                    .unwrap()
+                    .to_string();
+                response.widget_info(|| {
+                    WidgetInfo::labeled(
+                        WidgetType::Button,
+                        true,
+                        format!(
+                            "{}, {}, {}, Day {}",
+                            recipe_title,
+                            entry.portion.label(),
+                            slot_name,
+                            col_idx + 1
+                        ),
+                    )
+                });
+                if self.pending_focus == Some((col_idx, slot_idx, row_idx)) {
+                    response.request_focus();
+                    self.pending_focus = None;
+                }
+                if response.has_focus() {
+                    ui.painter()
+                        .rect_stroke(response.rect, 2., ui.visuals().selection.stroke);
+                    // Arrow keys move the entry instead of the keyboard focus.
+                    ui.memory_mut(|memory| {
+                        memory.set_focus_lock_filter(
+                            ui_item_id,
+                            EventFilter {
+                                horizontal_arrows: true,
+                                vertical_arrows: true,
+                                ..Default::default()
+                            },
+                        )
+                    });
+                    if let Some(action) = keyboard_action(ui) {
+                        if action == KeyboardAction::OpenMenu {
+                            self.context_menu_payload = Some(item_location);
+                            self.context_menu_pos = response.rect.left_bottom();
+                            self.show_context_menu = true;
+                        } else {
+                            self.keyboard_action = Some((item_location, action));
+                        }
+                    }
+                }
+                if is_selected {
+                    let fill = ui.visuals().selection.bg_fill.gamma_multiply(0.4);
+                    ui.painter().rect_filled(response.rect, 2., fill);
+                }
+                let response = response.on_hover_text(KEYBOARD_HINT);
+                if response.clicked() {
+                    self.select(ui, meal_planner, item_location);
+                }
+
+                if response.clicked_by(PointerButton::Secondary) {
+                    if let Some(pos) = pointer_pos {
+                        self.context_menu_payload = Some(item_location);
+                        self.context_menu_pos = pos;
+                    }
+                    self.show_context_menu = true;
+                }
+                // Detect drops onto this item:
+                if let (Some(pointer), Some(hovered_payload)) = (
+                    ui.input(|i| i.pointer.interact_pos()),
+                    response.dnd_hover_payload::<DragPayload>(),
+                ) {
+                    let rect = response.rect;
 
-            meal_planner.add_recipe_to_planner(to.col, to.row, recipe_id);
+                    // Preview insertion:
+                    let stroke = egui::Stroke::new(1.0, Color32::WHITE);
+                    let insert_row_idx = if hovered_payload.contains(&item_location) {
+                        // We are dragged onto ourselves
+                        ui.painter().hline(rect.x_range(), rect.center().y, stroke);
+                        row_idx
+                    } else if pointer.y < rect.center().y {
+                        // Above us
+                        ui.painter().hline(rect.x_range(), rect.top(), stroke);
+                        row_idx
+                    } else {
+                        // Below us
+                        ui.painter().hline(rect.x_range(), rect.bottom(), stroke);
+                        row_idx + 1
+                    };
+
+                    if let Some(dragged_payload) = response.dnd_release_payload::<DragPayload>() {
+                        // The user dropped onto this item.
+                        dropped = Some((
+                            (*dragged_payload).clone(),
+                            (col_idx, slot_idx, insert_row_idx),
+                        ));
+                    }
+                }
+            }
+        });
+
+        if let Some(dragged_payload) = dropped_payload {
+            // The user dropped onto the slot, but not on any one item: insert last.
+            dropped = Some(((*dragged_payload).clone(), (col_idx, slot_idx, usize::MAX)));
+        }
+
+        dropped
+    }
+}
+
+/// Editor for the portion of a plan entry. `serving_weight` is used to convert
+/// between servings and grams when switching modes.
+fn portion_ui(ui: &mut egui::Ui, portion: &mut Portion, serving_weight: f32) {
+    ui.label(hb("Portion"));
+    ui.horizontal(|ui| {
+        let is_servings = matches!(portion, Portion::Servings(_));
+        if ui.selectable_label(is_servings, "Servings").clicked() {
+            if let Portion::Grams(grams) = *portion {
+                let servings = if serving_weight > 0. {
+                    grams / serving_weight
+                } else {
+                    1.
+                };
+                *portion = Portion::Servings((servings * 4.).round().max(1.) / 4.);
+            }
+        }
+        if ui.selectable_label(!is_servings, "Grams").clicked() {
+            if let Portion::Servings(servings) = *portion {
+                *portion = Portion::Grams((servings * serving_weight).round().max(1.));
+            }
+        }
+    });
+    ui.horizontal(|ui| match portion {
+        Portion::Servings(servings) => {
+            for quick_pick in [0.5, 1., 1.5, 2.] {
+                if ui
+                    .selectable_label(*servings == quick_pick, quick_pick.to_string())
+                    .clicked()
+                {
+                    *servings = quick_pick;
+                }
+            }
+            ui.add(
+                DragValue::new(servings)
+                    .speed(0.25)
+                    .range(0.25..=20.)
+                    .suffix(" ×"),
+            );
+        }
+        Portion::Grams(grams) => {
+            ui.add(DragValue::new(grams).speed(5.).range(1..=5000).suffix("g"));
         }
+    });
+}
+
+/// Who of the household eats an entry, and how much each of them has.
+fn shares_ui(ui: &mut Ui, meal_planner: &mut MealPlanner, payload: Location) {
+    let location = (payload.col, payload.slot, payload.row);
+    let Some(entry) = meal_planner
+        .get_plan_entry(payload.col, payload.slot, payload.row)
+        .cloned()
+    else {
+        return;
+    };
+    let members = meal_planner.get_members().to_vec();
+    if members.is_empty() {
+        return;
+    }
+    ui.label(hb("Eaten by"));
+    Grid::new("planner_entry_shares")
+        .num_columns(2)
+        .show(ui, |ui| {
+            for member in members {
+                let share = entry.shares.iter().find(|share| share.member == member.id);
+                let mut portion = share.map_or(entry.portion, |share| share.portion);
+                let mut eats = entry.shares.is_empty() || share.is_some();
+                if ui.checkbox(&mut eats, &member.name).changed() {
+                    meal_planner.set_member_share(location, member.id, eats.then_some(portion));
+                }
+                let changed = match &mut portion {
+                    Portion::Servings(servings) => ui.add_enabled(
+                        eats,
+                        DragValue::new(servings)
+                            .speed(0.25)
+                            .range(0.25..=20.)
+                            .suffix(" ×"),
+                    ),
+                    Portion::Grams(grams) => ui.add_enabled(
+                        eats,
+                        DragValue::new(grams).speed(5.).range(1..=5000).suffix("g"),
+                    ),
+                }
+                .changed();
+                if changed {
+                    meal_planner.set_member_share(location, member.id, Some(portion));
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// Names and portions of the members eating an entry, when not everyone eats the
+/// same portion.
+fn shares_text(meal_planner: &MealPlanner, entry: &PlanEntry) -> Option<String> {
+    if entry.shares.is_empty() {
+        return None;
+    }
+    let text = entry
+        .shares
+        .iter()
+        .filter_map(|share| {
+            let member = meal_planner
+                .get_members()
+                .iter()
+                .find(|member| member.id == share.member)?;
+            Some(format!("{} ({})", member.name, share.portion.label()))
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+    Some(if text.is_empty() {
+        "Nobody".to_string()
+    } else {
+        text
+    })
+}
+
+/// Daily calories and macros of each household member against their targets.
+fn members_day_ui(ui: &mut Ui, meal_planner: &MealPlanner, day_idx: usize, day: &DayPlan) {
+    Grid::new(("planner_members", day_idx))
+        .num_columns(2)
+        .show(ui, |ui| {
+            for member in meal_planner.get_members() {
+                let macros = day_macros(meal_planner, day, Some(&member.id));
+                let targets = member.targets;
+                ui.label(hb(&member.name));
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 2.;
+                    ui.label(target_text(macros.calories, targets.calories));
+                    ui.label(hs(&format!("/ {:.0} kcal ·", targets.calories)));
+                    ui.label(hs("P"));
+                    ui.label(target_text(macros.protein, targets.protein));
+                    ui.label(hs("· C"));
+                    ui.label(target_text(macros.carbs, targets.carbs));
+                    ui.label(hs("· F"));
+                    ui.label(target_text(macros.fat, targets.fat));
+                });
+                ui.end_row();
+            }
+        });
+}
+
+fn servings_text(servings: f32) -> String {
+    format!("{}", (servings * 10.).round() / 10.)
+}
+
+/// Shows where an entry stands in its cooking event, warning when the leftovers run
+/// out or are never eaten.
+fn batch_ui(ui: &mut Ui, usage: &BatchUsage, location: (usize, usize, usize)) {
+    let Some(status) = entry_status(usage, location) else {
+        return;
+    };
+    let text = match status {
+        EntryBatchStatus::Cooks {
+            servings,
+            remaining,
+        } => format!(
+            "{} Cook {} servings, {} left over",
+            ICON_COOKING_POT,
+            servings_text(servings),
+            servings_text(remaining)
+        ),
+        EntryBatchStatus::Leftovers { remaining } => format!(
+            "{} Leftovers, {} left",
+            ICON_REFRIGERATOR,
+            servings_text(remaining)
+        ),
+        EntryBatchStatus::RunsOut { missing } => format!(
+            "{} Leftovers run out, {} servings short",
+            ICON_TRIANGLE_ALERT,
+            servings_text(missing)
+        ),
+    };
+    let color = if status.is_warning() {
+        ui.visuals().error_fg_color
+    } else {
+        ui.visuals().weak_text_color()
+    };
+    ui.label(hs(&text).color(color));
+
+    let is_last = usage
+        .entries
+        .last()
+        .is_some_and(|(last, _)| *last == location);
+    if let Some(uneaten) = uneaten_servings(usage).filter(|_| is_last) {
+        ui.label(
+            hs(&format!(
+                "{} {} servings are never eaten",
+                ICON_TRIANGLE_ALERT,
+                servings_text(uneaten)
+            ))
+            .color(ui.visuals().warn_fg_color),
+        );
+    }
+}
+
+/// Reads the planner shortcuts pressed this frame.
+fn keyboard_action(ui: &egui::Ui) -> Option<KeyboardAction> {
+    ui.input_mut(|input| {
+        let shortcuts = [
+            (
+                Modifiers::COMMAND,
+                Key::ArrowLeft,
+                KeyboardAction::CopyToPreviousDay,
+            ),
+            (
+                Modifiers::COMMAND,
+                Key::ArrowRight,
+                KeyboardAction::CopyToNextDay,
+            ),
+            (Modifiers::COMMAND, Key::D, KeyboardAction::Duplicate),
+            (Modifiers::NONE, Key::ArrowUp, KeyboardAction::Up),
+            (Modifiers::NONE, Key::ArrowDown, KeyboardAction::Down),
+            (Modifiers::NONE, Key::ArrowLeft, KeyboardAction::PreviousDay),
+            (Modifiers::NONE, Key::ArrowRight, KeyboardAction::NextDay),
+            (Modifiers::NONE, Key::Delete, KeyboardAction::Remove),
+            (Modifiers::NONE, Key::Backspace, KeyboardAction::Remove),
+            (Modifiers::NONE, Key::Enter, KeyboardAction::OpenMenu),
+            (Modifiers::SHIFT, Key::F10, KeyboardAction::OpenMenu),
+        ];
+        shortcuts
+            .into_iter()
+            .find(|(modifiers, key, _)| input.consume_key(*modifiers, *key))
+            .map(|(_, _, action)| action)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plan with entries `a` and `b` in the first slot of the first day.
+    fn two_entries() -> (Planner, MealPlanner, Location, Location) {
+        let mut meal_planner = MealPlanner::default();
+        let mut add_recipe = |title: &str| {
+            let recipe = meal_planner.create_draft_recipe().unwrap();
+            recipe.title = title.to_string();
+            let id = recipe.id;
+            meal_planner.delete_draft_recipe();
+            id
+        };
+        let (a, b) = (add_recipe("a"), add_recipe("b"));
+        meal_planner.add_recipe_to_planner(0, 0, 0, PlanEntry::new(a));
+        meal_planner.add_recipe_to_planner(0, 0, 1, PlanEntry::new(b));
+        let location = |row, recipe_id| Location {
+            col: 0,
+            slot: 0,
+            row,
+            recipe_id,
+        };
+        (
+            Planner::default(),
+            meal_planner,
+            location(0, a),
+            location(1, b),
+        )
+    }
+
+    fn recipe_at(meal_planner: &MealPlanner, day: usize, slot: usize, row: usize) -> Option<Uuid> {
+        meal_planner
+            .get_plan_entry(day, slot, row)
+            .map(|entry| entry.recipe_id)
+    }
+
+    #[test]
+    fn moves_cross_slot_boundaries() {
+        let (mut planner, mut meal_planner, a, b) = two_entries();
+        planner.apply_keyboard_action(&mut meal_planner, b, KeyboardAction::Down);
+        assert_eq!(recipe_at(&meal_planner, 0, 1, 0), Some(b.recipe_id));
+        assert_eq!(planner.pending_focus, Some((0, 1, 0)));
+
+        planner.apply_keyboard_action(&mut meal_planner, a, KeyboardAction::Down);
+        assert_eq!(recipe_at(&meal_planner, 0, 1, 0), Some(a.recipe_id));
+        assert_eq!(recipe_at(&meal_planner, 0, 1, 1), Some(b.recipe_id));
+
+        let b = Location {
+            slot: 1,
+            row: 1,
+            ..b
+        };
+        planner.apply_keyboard_action(&mut meal_planner, b, KeyboardAction::Up);
+        assert_eq!(recipe_at(&meal_planner, 0, 1, 0), Some(b.recipe_id));
+
+        let a = Location {
+            slot: 1,
+            row: 1,
+            ..a
+        };
+        planner.apply_keyboard_action(&mut meal_planner, a, KeyboardAction::Up);
+        assert_eq!(recipe_at(&meal_planner, 0, 1, 0), Some(a.recipe_id));
+        let a = Location { row: 0, ..a };
+        planner.apply_keyboard_action(&mut meal_planner, a, KeyboardAction::Up);
+        assert_eq!(recipe_at(&meal_planner, 0, 0, 0), Some(a.recipe_id));
+        assert_eq!(planner.pending_focus, Some((0, 0, 0)));
+    }
+
+    #[test]
+    fn moves_stop_at_the_edges_of_the_plan() {
+        let (mut planner, mut meal_planner, a, _) = two_entries();
+        let before = meal_planner.get_daily_plan().clone();
+        planner.apply_keyboard_action(&mut meal_planner, a, KeyboardAction::Up);
+        planner.apply_keyboard_action(&mut meal_planner, a, KeyboardAction::PreviousDay);
+        planner.apply_keyboard_action(&mut meal_planner, a, KeyboardAction::CopyToPreviousDay);
+        assert_eq!(meal_planner.get_daily_plan(), &before);
+        assert_eq!(planner.pending_focus, None);
+
+        let last_day = meal_planner.plan_length() - 1;
+        let last_slot = meal_planner.get_meal_slots().len() - 1;
+        let entry = PlanEntry::new(Uuid::new_v4());
+        meal_planner.add_recipe_to_planner(last_day, last_slot, 0, entry.clone());
+        let last = Location {
+            col: last_day,
+            slot: last_slot,
+            row: 0,
+            recipe_id: entry.recipe_id,
+        };
+        let before = meal_planner.get_daily_plan().clone();
+        planner.apply_keyboard_action(&mut meal_planner, last, KeyboardAction::Down);
+        planner.apply_keyboard_action(&mut meal_planner, last, KeyboardAction::NextDay);
+        planner.apply_keyboard_action(&mut meal_planner, last, KeyboardAction::CopyToNextDay);
+        assert_eq!(meal_planner.get_daily_plan(), &before);
+    }
+
+    #[test]
+    fn moves_and_copies_across_days() {
+        let (mut planner, mut meal_planner, a, b) = two_entries();
+        planner.apply_keyboard_action(&mut meal_planner, b, KeyboardAction::NextDay);
+        assert_eq!(recipe_at(&meal_planner, 1, 0, 0), Some(b.recipe_id));
+        assert_eq!(recipe_at(&meal_planner, 0, 0, 1), None);
+
+        let b = Location {
+            col: 1,
+            row: 0,
+            ..b
+        };
+        planner.apply_keyboard_action(&mut meal_planner, b, KeyboardAction::PreviousDay);
+        assert_eq!(recipe_at(&meal_planner, 0, 0, 0), Some(b.recipe_id));
+        assert_eq!(planner.pending_focus, Some((0, 0, 0)));
+
+        let a = Location { row: 1, ..a };
+        planner.apply_keyboard_action(&mut meal_planner, a, KeyboardAction::CopyToNextDay);
+        assert_eq!(recipe_at(&meal_planner, 0, 0, 1), Some(a.recipe_id));
+        assert_eq!(recipe_at(&meal_planner, 1, 0, 0), Some(a.recipe_id));
+
+        planner.apply_keyboard_action(&mut meal_planner, a, KeyboardAction::Duplicate);
+        assert_eq!(recipe_at(&meal_planner, 0, 0, 2), Some(a.recipe_id));
+        assert_eq!(planner.pending_focus, Some((0, 0, 2)));
+    }
+
+    #[test]
+    fn remove_prunes_the_cooking_event() {
+        let (mut planner, mut meal_planner, a, b) = two_entries();
+        meal_planner.start_batch(0, 0, 0);
+        let batch = meal_planner.get_plan_entry(0, 0, 0).unwrap().batch.unwrap();
+        planner.apply_keyboard_action(&mut meal_planner, a, KeyboardAction::Down);
+        assert!(meal_planner.get_batch(&batch).is_some());
+
+        let a = Location { row: 1, ..a };
+        planner.apply_keyboard_action(&mut meal_planner, a, KeyboardAction::Remove);
+        assert!(meal_planner.get_batch(&batch).is_none());
+        assert_eq!(recipe_at(&meal_planner, 0, 0, 0), Some(b.recipe_id));
+        assert_eq!(planner.pending_focus, Some((0, 0, 0)));
     }
-    // ui.interact(ui.clip_rect(), ui.id(), Sense::click_and_drag())
 }
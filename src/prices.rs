@@ -0,0 +1,589 @@
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+};
+
+use egui::{Button, DragValue, Grid};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    food_names::FoodNames,
+    leftovers::batch_usage,
+    meal_planner::MealPlanner,
+    models::{ParsedNutrient, Recipe},
+    plan::DayPlan,
+    shopping_list::parsed_quantity,
+    typography::icons::{ICON_FILE_UP, ICON_PLUS, ICON_TRASH_2},
+    units::{Quantity, Unit},
+    util::{hb, hs, open_file, DEFAULT_PADDING},
+};
+
+/// Days the weekly budget is counted over.
+pub const DAYS_PER_WEEK: usize = 7;
+
+/// What a food costs at the store: `cost` for `quantity` of it, a package or a
+/// single unit.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FoodPrice {
+    pub id: Uuid,
+    pub food: String,
+    pub quantity: Quantity,
+    pub cost: f32,
+}
+
+impl FoodPrice {
+    pub fn new(food: &str, quantity: Quantity, cost: f32) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            food: food.trim().to_string(),
+            quantity,
+            cost,
+        }
+    }
+
+    pub fn is_food(&self, food: &str, names: &FoodNames) -> bool {
+        names.same_food(&self.food, food)
+    }
+
+    /// Cost of `quantity` of the food. Goes through the weight when the units measure
+    /// different things, `grams` being what the quantity weighs if it is known.
+    pub fn cost_of(&self, quantity: &Quantity, grams: f32) -> Option<f32> {
+        if self.quantity.amount <= 0. {
+            return None;
+        }
+        let share = match quantity.convert(&self.quantity.unit) {
+            Some(same) => same.amount / self.quantity.amount,
+            None => {
+                let priced_grams = self.quantity.convert(&Unit::Gram)?.amount;
+                if grams <= 0. {
+                    return None;
+                }
+                grams / priced_grams
+            }
+        };
+        Some(share * self.cost)
+    }
+}
+
+/// An estimated cost, with the foods it leaves out for lack of a price.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cost {
+    pub amount: f32,
+    pub unpriced: BTreeSet<String>,
+}
+
+impl Cost {
+    pub fn add(&mut self, other: Cost) {
+        self.amount += other.amount;
+        self.unpriced.extend(other.unpriced);
+    }
+
+    pub fn scaled(mut self, factor: f32) -> Cost {
+        self.amount *= factor;
+        self
+    }
+
+    /// Whether every food in it has a price.
+    pub fn is_complete(&self) -> bool {
+        self.unpriced.is_empty()
+    }
+
+    /// The amount, with a hint of what is missing from it.
+    pub fn label(&self) -> String {
+        if self.is_complete() {
+            format_cost(self.amount)
+        } else {
+            format!("{}+", format_cost(self.amount))
+        }
+    }
+
+    /// Explains an incomplete cost.
+    pub fn unpriced_text(&self) -> String {
+        let foods: Vec<&str> = self.unpriced.iter().map(String::as_str).collect();
+        format!("Without a price: {}", foods.join(", "))
+    }
+}
+
+pub fn format_cost(amount: f32) -> String {
+    format!("{:.2}", amount)
+}
+
+fn ingredient_cost(model: &ParsedNutrient, meal_planner: &MealPlanner) -> Cost {
+    let quantity = parsed_quantity(model);
+    let names = meal_planner.get_food_names();
+    let cost = meal_planner
+        .get_prices()
+        .iter()
+        .find(|price| price.is_food(&model.food, names))
+        .and_then(|price| price.cost_of(&quantity, model.weight));
+    match cost {
+        Some(amount) => Cost {
+            amount,
+            unpriced: BTreeSet::new(),
+        },
+        None => Cost {
+            amount: 0.,
+            unpriced: BTreeSet::from([names.display_name(&model.food).to_lowercase()]),
+        },
+    }
+}
+
+/// Cost of cooking the whole recipe.
+pub fn recipe_cost(recipe: &Recipe, meal_planner: &MealPlanner) -> Cost {
+    let mut cost = Cost::default();
+    for model in recipe
+        .macros
+        .ingredients
+        .iter()
+        .flat_map(|ingr| ingr.parsed.iter().flatten())
+    {
+        cost.add(ingredient_cost(model, meal_planner));
+    }
+    cost
+}
+
+pub fn cost_per_serving(recipe: &Recipe, meal_planner: &MealPlanner) -> Cost {
+    recipe_cost(recipe, meal_planner).scaled(1. / recipe.servings.max(1) as f32)
+}
+
+/// What each plan day costs to cook. Batch cooked meals count on the day they are
+/// cooked, like on the shopping list.
+pub fn day_costs(plan: &[DayPlan], meal_planner: &MealPlanner) -> Vec<Cost> {
+    let usage = batch_usage(plan, meal_planner);
+    let mut costs = vec![Cost::default(); plan.len()];
+    for (day_idx, day) in plan.iter().enumerate() {
+        for (slot_idx, slot) in day.slots.iter().enumerate() {
+            for (row_idx, entry) in slot.iter().enumerate() {
+                let Some(recipe) = meal_planner.get_recipe_by_id(&entry.recipe_id) else {
+                    continue;
+                };
+                let batch = entry
+                    .batch
+                    .and_then(|id| Some((usage.get(&id)?, meal_planner.get_batch(&id)?)));
+                let servings = match batch {
+                    Some((usage, _))
+                        if usage.cook_location() != Some((day_idx, slot_idx, row_idx)) =>
+                    {
+                        continue
+                    }
+                    Some((_, batch)) => batch.servings,
+                    None => meal_planner.entry_servings(entry, recipe),
+                };
+                costs[day_idx].add(meal_planner.cost_per_serving(recipe).scaled(servings));
+            }
+        }
+    }
+    costs
+}
+
+/// Splits a CSV line into its fields, undoing the quoting of fields with commas.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => field.push(c),
+        }
+    }
+    fields
+}
+
+/// Reads a number, ignoring a currency symbol in front of it.
+fn parse_number(text: &str) -> Option<f32> {
+    text.trim()
+        .trim_start_matches(|c: char| !c.is_ascii_digit() && c != '.' && c != '-')
+        .parse()
+        .ok()
+}
+
+fn parse_price(line: &str) -> Option<FoodPrice> {
+    let fields = csv_fields(line);
+    let [food, amount, unit, cost] = fields.as_slice() else {
+        return None;
+    };
+    let amount = parse_number(amount)?;
+    let cost = parse_number(cost)?;
+    if food.trim().is_empty() || amount <= 0. || cost < 0. {
+        return None;
+    }
+    Some(FoodPrice::new(
+        food,
+        Quantity::new(amount, Unit::parse(unit)),
+        cost,
+    ))
+}
+
+/// Prices from CSV lines of ingredient, amount, unit and price, and the number of
+/// lines that could not be read. A header line is skipped.
+pub fn parse_prices_csv(text: &str) -> (Vec<FoodPrice>, usize) {
+    let mut prices = vec![];
+    let mut skipped = 0;
+    for (idx, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_price(line) {
+            Some(price) => prices.push(price),
+            None if idx == 0 => {}
+            None => skipped += 1,
+        }
+    }
+    (prices, skipped)
+}
+
+/// Lists and edits the prices of foods, and the weekly budget.
+#[derive(Debug)]
+pub struct PricesView {
+    food: String,
+    amount: f32,
+    unit: String,
+    cost: f32,
+    import: Arc<Mutex<Option<Vec<u8>>>>,
+    import_result: Option<String>,
+}
+
+impl Default for PricesView {
+    fn default() -> Self {
+        Self {
+            food: String::new(),
+            amount: 1.,
+            unit: String::new(),
+            cost: 0.,
+            import: Arc::new(Mutex::new(None)),
+            import_result: None,
+        }
+    }
+}
+
+impl PricesView {
+    fn poll_import(&mut self, meal_planner: &mut MealPlanner) {
+        let Some(content) = self.import.lock().ok().and_then(|mut lock| lock.take()) else {
+            return;
+        };
+        let (prices, skipped) = parse_prices_csv(&String::from_utf8_lossy(&content));
+        let mut result = format!(
+            "Imported {} price{}.",
+            prices.len(),
+            if prices.len() == 1 { "" } else { "s" }
+        );
+        if skipped > 0 {
+            result.push_str(&format!(" {} lines could not be read.", skipped));
+        }
+        for price in prices {
+            meal_planner.set_price(price);
+        }
+        self.import_result = Some(result);
+    }
+
+    fn budget_ui(ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        ui.horizontal(|ui| {
+            let mut has_budget = meal_planner.weekly_budget.is_some();
+            if ui.checkbox(&mut has_budget, "Weekly budget").changed() {
+                meal_planner.weekly_budget = has_budget.then_some(100.);
+            }
+            if let Some(budget) = meal_planner.weekly_budget.as_mut() {
+                ui.add(
+                    DragValue::new(budget)
+                        .range(0.0..=100000.)
+                        .speed(1.)
+                        .fixed_decimals(2),
+                );
+            }
+        });
+    }
+
+    fn add_ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        ui.label(hb("Add a price"));
+        Grid::new("price_add").num_columns(2).show(ui, |ui| {
+            ui.label("Ingredient");
+            ui.text_edit_singleline(&mut self.food);
+            ui.end_row();
+            ui.label("For");
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut self.amount)
+                        .range(0.0..=100000.)
+                        .speed(0.5),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.unit)
+                        .hint_text("g, ml, piece, …")
+                        .desired_width(80.),
+                );
+            })
+            .response
+            .on_hover_text("A package size, like 200 g, or 1 piece for a price per unit");
+            ui.end_row();
+            ui.label("Price");
+            ui.add(
+                DragValue::new(&mut self.cost)
+                    .range(0.0..=100000.)
+                    .speed(0.1)
+                    .fixed_decimals(2),
+            );
+            ui.end_row();
+        });
+        let food = self.food.trim();
+        let exists = meal_planner
+            .get_prices()
+            .iter()
+            .any(|price| price.is_food(food, meal_planner.get_food_names()));
+        let response = ui.add_enabled(
+            !food.is_empty() && self.amount > 0.,
+            Button::new(format!("{} Save price", ICON_PLUS)),
+        );
+        let response = if exists {
+            response.on_hover_text("Replaces the price of the same ingredient")
+        } else {
+            response
+        };
+        if response.clicked() {
+            let quantity = Quantity::new(self.amount, Unit::parse(&self.unit));
+            meal_planner.set_price(FoodPrice::new(food, quantity, self.cost));
+            self.food.clear();
+        }
+    }
+
+    fn import_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button(format!("{} Import CSV", ICON_FILE_UP)).clicked() {
+                self.import_result = None;
+                open_file("CSV", &["csv", "txt"], self.import.clone());
+            }
+            ui.label(hs(
+                "Columns: ingredient, amount, unit, price, e.g. spinach,200,g,1.49",
+            ));
+        });
+        if let Some(result) = &self.import_result {
+            ui.label(hs(result));
+        }
+    }
+
+    /// Foods in the planned recipes that have no price yet, to fill in the form with.
+    fn unpriced_ui(&mut self, ui: &mut egui::Ui, meal_planner: &MealPlanner) {
+        let plan = meal_planner.get_daily_plan();
+        let mut unpriced = BTreeSet::new();
+        for cost in day_costs(plan, meal_planner) {
+            unpriced.extend(cost.unpriced);
+        }
+        if unpriced.is_empty() {
+            return;
+        }
+        ui.label(hb("Planned without a price"));
+        ui.horizontal_wrapped(|ui| {
+            for food in unpriced {
+                if ui.small_button(&food).clicked() {
+                    self.food = food;
+                }
+            }
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        self.poll_import(meal_planner);
+
+        Self::budget_ui(ui, meal_planner);
+        ui.add_space(DEFAULT_PADDING);
+        ui.group(|ui| self.add_ui(ui, meal_planner));
+        self.import_ui(ui);
+        ui.add_space(DEFAULT_PADDING);
+        self.unpriced_ui(ui, meal_planner);
+        ui.add_space(DEFAULT_PADDING);
+
+        if meal_planner.get_prices().is_empty() {
+            ui.label(hs(
+                "No prices yet. Add what ingredients cost to see what recipes and the plan cost.",
+            ));
+            return;
+        }
+
+        let mut remove = None;
+        Grid::new("prices")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(hb("Ingredient"));
+                ui.label(hb("For"));
+                ui.label(hb("Price"));
+                ui.end_row();
+                for mut price in meal_planner.get_prices().to_vec() {
+                    ui.label(&price.food);
+                    let mut changed = ui
+                        .horizontal(|ui| {
+                            let response = ui.add(
+                                DragValue::new(&mut price.quantity.amount)
+                                    .range(0.0..=100000.)
+                                    .speed(0.5),
+                            );
+                            ui.label(price.quantity.unit.symbol(price.quantity.amount));
+                            response.changed()
+                        })
+                        .inner;
+                    changed |= ui
+                        .add(
+                            DragValue::new(&mut price.cost)
+                                .range(0.0..=100000.)
+                                .speed(0.1)
+                                .fixed_decimals(2),
+                        )
+                        .changed();
+                    if ui
+                        .button(ICON_TRASH_2)
+                        .on_hover_text("Remove price")
+                        .clicked()
+                    {
+                        remove = Some(price.id);
+                    }
+                    ui.end_row();
+                    if changed {
+                        meal_planner.set_price(price);
+                    }
+                }
+            });
+        if let Some(id) = remove {
+            meal_planner.remove_price(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ingredient;
+
+    #[test]
+    fn csv_fields_undo_quoting() {
+        assert_eq!(
+            csv_fields(r#""Cheese, cheddar",200,g,"$3.50""#),
+            vec!["Cheese, cheddar", "200", "g", "$3.50"]
+        );
+        assert_eq!(
+            csv_fields(r#""12"" pizza base",1,,2"#),
+            vec!["12\" pizza base", "1", "", "2"]
+        );
+        assert_eq!(csv_fields(""), vec![""]);
+    }
+
+    #[test]
+    fn parse_number_ignores_currency() {
+        assert_eq!(parse_number(" $3.50 "), Some(3.5));
+        assert_eq!(parse_number("€12"), Some(12.));
+        assert_eq!(parse_number("-1"), Some(-1.));
+        assert_eq!(parse_number("free"), None);
+    }
+
+    #[test]
+    fn parse_prices_csv_skips_header_and_bad_lines() {
+        let csv = "Ingredient,Amount,Unit,Price\n\
+                   \"Cheese, cheddar\",200,g,$3.50\n\
+                   \n\
+                   eggs,12,,4\n\
+                   milk,-1,l,1\n\
+                   rice,1,kg\n";
+        let (prices, skipped) = parse_prices_csv(csv);
+        assert_eq!(skipped, 2);
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices[0].food, "Cheese, cheddar");
+        assert_eq!(prices[0].quantity, Quantity::grams(200.));
+        assert_eq!(prices[0].cost, 3.5);
+        assert_eq!(prices[1].quantity.unit, Unit::Piece(String::new()));
+
+        let (prices, skipped) = parse_prices_csv("eggs,12,,4");
+        assert_eq!((prices.len(), skipped), (1, 0));
+    }
+
+    #[test]
+    fn cost_of_converts_units() {
+        let flour = FoodPrice::new("flour", Quantity::new(1., Unit::Kilogram), 2.);
+        assert_eq!(flour.cost_of(&Quantity::grams(250.), 250.), Some(0.5));
+        assert_eq!(
+            flour.cost_of(&Quantity::new(2., Unit::Cup), 250.),
+            Some(0.5)
+        );
+        assert_eq!(flour.cost_of(&Quantity::new(2., Unit::Cup), 0.), None);
+
+        let eggs = FoodPrice::new("eggs", Quantity::new(12., Unit::parse("")), 3.);
+        assert_eq!(
+            eggs.cost_of(&Quantity::new(4., Unit::parse("")), 200.),
+            Some(1.)
+        );
+        assert_eq!(eggs.cost_of(&Quantity::grams(100.), 100.), None);
+
+        let free = FoodPrice::new("water", Quantity::new(0., Unit::Litre), 0.);
+        assert_eq!(free.cost_of(&Quantity::new(1., Unit::Litre), 1000.), None);
+    }
+
+    #[test]
+    fn incomplete_costs_are_marked() {
+        let mut cost = Cost {
+            amount: 2.,
+            unpriced: BTreeSet::new(),
+        };
+        assert_eq!(cost.label(), "2.00");
+        cost.add(Cost {
+            amount: 1.5,
+            unpriced: BTreeSet::from(["saffron".to_string()]),
+        });
+        assert_eq!(cost.label(), "3.50+");
+        assert_eq!(cost.unpriced_text(), "Without a price: saffron");
+        assert_eq!(cost.scaled(0.5).amount, 1.75);
+    }
+
+    fn parsed(food: &str, quantity: f32, measure: &str, weight: f32) -> ParsedNutrient {
+        ParsedNutrient {
+            quantity,
+            measure: Some(measure.to_string()),
+            foodMatch: None,
+            food: food.to_string(),
+            foodId: String::new(),
+            foodCategory: None,
+            weight,
+            retainedWeight: weight,
+            nutrients: Default::default(),
+            measureURI: None,
+            status: String::new(),
+        }
+    }
+
+    #[test]
+    fn serving_costs_follow_price_changes() {
+        let mut meal_planner = MealPlanner::default();
+        let recipe = meal_planner.create_draft_recipe().unwrap();
+        recipe.servings = 2;
+        recipe.macros.ingredients = vec![Ingredient {
+            text: "500 g rice, 1 pinch saffron".to_string(),
+            parsed: Some(vec![
+                parsed("rice", 500., "gram", 500.),
+                parsed("saffron", 1., "pinch", 0.1),
+            ]),
+        }];
+        let recipe_id = recipe.id;
+        let recipe = meal_planner.get_recipe_by_id(&recipe_id).unwrap().clone();
+
+        let cost = meal_planner.cost_per_serving(&recipe);
+        assert_eq!(cost.amount, 0.);
+        assert_eq!(cost.unpriced.len(), 2);
+
+        meal_planner.set_price(FoodPrice::new(
+            "Rice",
+            Quantity::new(1., Unit::Kilogram),
+            3.,
+        ));
+        let cost = meal_planner.cost_per_serving(&recipe);
+        assert_eq!(cost.amount, 0.75);
+        assert_eq!(cost.label(), "0.75+");
+
+        meal_planner.edit_recipe(&recipe_id, |recipe| recipe.servings = 3);
+        let recipe = meal_planner.get_recipe_by_id(&recipe_id).unwrap().clone();
+        assert_eq!(meal_planner.cost_per_serving(&recipe).amount, 0.5);
+    }
+}
@@ -0,0 +1,350 @@
+use uuid::Uuid;
+
+use crate::{
+    meal_planner::MealPlanner,
+    models::Recipe,
+    pdf::{wrap, Document, Font, A4_LANDSCAPE, A4_PORTRAIT},
+    summary::day_macros,
+};
+
+const MARGIN: f32 = 36.;
+const DAYS_PER_PAGE: usize = 7;
+const SLOT_LABEL_WIDTH: f32 = 70.;
+const HEADER_HEIGHT: f32 = 28.;
+const TOTALS_HEIGHT: f32 = 40.;
+const CELL_PADDING: f32 = 4.;
+const CELL_FONT_SIZE: f32 = 8.;
+const CARD_MARGIN: f32 = 50.;
+
+/// Cuts `lines` down to `max` lines, marking the cut on the last one. A cell too
+/// small for any line still shows the first one, so it doesn't look empty.
+fn truncate_lines(mut lines: Vec<String>, max: usize) -> Vec<String> {
+    if lines.len() > max {
+        lines.truncate(max.max(1));
+        if let Some(last) = lines.last_mut() {
+            last.push('…');
+        }
+    }
+    lines
+}
+
+/// One landscape page per week of the plan, meal slots as rows and days as columns.
+fn week_pages(document: &mut Document, meal_planner: &MealPlanner) {
+    let plan = meal_planner.get_daily_plan();
+    let slot_names = meal_planner.get_meal_slots();
+    let line_height = CELL_FONT_SIZE + 2.;
+
+    for (week_idx, week) in plan.chunks(DAYS_PER_PAGE).enumerate() {
+        let first_day = week_idx * DAYS_PER_PAGE;
+        let page = document.add_page(A4_LANDSCAPE);
+        let (width, height) = (page.width(), page.height());
+        let title = format!(
+            "Meal plan {} – {}",
+            meal_planner.plan_day_date(first_day).format("%-d %b"),
+            meal_planner
+                .plan_day_date(first_day + week.len() - 1)
+                .format("%-d %b %Y")
+        );
+        page.text(MARGIN, MARGIN + 12., 16., Font::Bold, &title);
+
+        let top = MARGIN + 28.;
+        let bottom = height - MARGIN;
+        let day_width = (width - 2. * MARGIN - SLOT_LABEL_WIDTH) / DAYS_PER_PAGE as f32;
+        let slot_height =
+            (bottom - top - HEADER_HEIGHT - TOTALS_HEIGHT) / slot_names.len().max(1) as f32;
+        let grid_right = MARGIN + SLOT_LABEL_WIDTH + day_width * week.len() as f32;
+        let row_top = |row: usize| top + HEADER_HEIGHT + slot_height * row as f32;
+
+        // Header with the days.
+        page.fill_rect((MARGIN, top), (grid_right - MARGIN, HEADER_HEIGHT), 0.92);
+        for (offset, _) in week.iter().enumerate() {
+            let x = MARGIN + SLOT_LABEL_WIDTH + day_width * offset as f32 + CELL_PADDING;
+            let day_idx = first_day + offset;
+            page.text(
+                x,
+                top + 12.,
+                10.,
+                Font::Bold,
+                &format!("Day {}", day_idx + 1),
+            );
+            let date = meal_planner.plan_day_date(day_idx).format("%a %-d %b");
+            page.text(x, top + 23., 8., Font::Regular, &date.to_string());
+        }
+
+        // Meals.
+        let max_lines = ((slot_height - 2. * CELL_PADDING) / line_height) as usize;
+        for (slot_idx, slot_name) in slot_names.iter().enumerate() {
+            let y = row_top(slot_idx);
+            for (line_idx, line) in wrap(slot_name, 9., SLOT_LABEL_WIDTH - 2. * CELL_PADDING)
+                .iter()
+                .enumerate()
+            {
+                let line_y = y + CELL_PADDING + 9. + line_idx as f32 * 11.;
+                page.text(MARGIN + CELL_PADDING, line_y, 9., Font::Bold, line);
+            }
+            for (offset, day) in week.iter().enumerate() {
+                let Some(slot) = day.slots.get(slot_idx) else {
+                    continue;
+                };
+                let cell_width = day_width - 2. * CELL_PADDING;
+                let lines: Vec<String> = slot
+                    .iter()
+                    .filter_map(|entry| {
+                        let recipe = meal_planner.get_recipe_by_id(&entry.recipe_id)?;
+                        let text = if entry.portion.is_single_serving() {
+                            recipe.to_string()
+                        } else {
+                            format!("{} ({})", recipe, entry.portion.label())
+                        };
+                        Some(wrap(&format!("• {}", text), CELL_FONT_SIZE, cell_width))
+                    })
+                    .flatten()
+                    .collect();
+                let x = MARGIN + SLOT_LABEL_WIDTH + day_width * offset as f32 + CELL_PADDING;
+                for (line_idx, line) in truncate_lines(lines, max_lines).iter().enumerate() {
+                    let line_y = y + CELL_PADDING + CELL_FONT_SIZE + line_idx as f32 * line_height;
+                    page.text(x, line_y, CELL_FONT_SIZE, Font::Regular, line);
+                }
+            }
+        }
+
+        // Daily totals.
+        let totals_top = row_top(slot_names.len());
+        page.fill_rect(
+            (MARGIN, totals_top),
+            (grid_right - MARGIN, TOTALS_HEIGHT),
+            0.96,
+        );
+        page.text(
+            MARGIN + CELL_PADDING,
+            totals_top + 13.,
+            9.,
+            Font::Bold,
+            "Total",
+        );
+        for (offset, day) in week.iter().enumerate() {
+            let macros = day_macros(meal_planner, day, None);
+            let x = MARGIN + SLOT_LABEL_WIDTH + day_width * offset as f32 + CELL_PADDING;
+            page.text(
+                x,
+                totals_top + 13.,
+                9.,
+                Font::Bold,
+                &format!("{:.0} kcal", macros.calories),
+            );
+            page.text(
+                x,
+                totals_top + 24.,
+                CELL_FONT_SIZE,
+                Font::Regular,
+                &format!("P {:.0}g · C {:.0}g", macros.protein, macros.carbs),
+            );
+            page.text(
+                x,
+                totals_top + 34.,
+                CELL_FONT_SIZE,
+                Font::Regular,
+                &format!("F {:.0}g", macros.fat),
+            );
+        }
+
+        // Grid lines.
+        let grid_bottom = totals_top + TOTALS_HEIGHT;
+        for row in 0..=slot_names.len() {
+            let y = row_top(row);
+            page.line((MARGIN, y), (grid_right, y), 0.5);
+        }
+        page.line((MARGIN, top), (grid_right, top), 0.5);
+        page.line((MARGIN, grid_bottom), (grid_right, grid_bottom), 0.5);
+        for column in 0..=week.len() {
+            let x = MARGIN + SLOT_LABEL_WIDTH + day_width * column as f32;
+            page.line((x, top), (x, grid_bottom), 0.5);
+        }
+        page.line((MARGIN, top), (MARGIN, grid_bottom), 0.5);
+    }
+}
+
+/// A line of a recipe card: font, size, indentation and text.
+type CardLine = (Font, f32, f32, String);
+
+fn card_lines(meal_planner: &MealPlanner, recipe: &Recipe, width: f32) -> Vec<CardLine> {
+    let mut lines: Vec<CardLine> = vec![];
+    let push_wrapped = |lines: &mut Vec<CardLine>, font, size, indent, text: &str| {
+        for line in wrap(text, size, width - indent) {
+            lines.push((font, size, indent, line));
+        }
+    };
+
+    push_wrapped(&mut lines, Font::Bold, 18., 0., &recipe.to_string());
+    let mut serves = format!(
+        "Serves {} · per serving {}",
+        recipe.servings,
+        recipe.macros_summary()
+    );
+    let cost = meal_planner.cost_per_serving(recipe);
+    if cost.amount > 0. {
+        serves.push_str(&format!(" · {}", cost.label()));
+    }
+    push_wrapped(&mut lines, Font::Regular, 10., 0., &serves);
+
+    let planned: Vec<String> = meal_planner
+        .get_daily_plan()
+        .iter()
+        .enumerate()
+        .flat_map(|(day_idx, day)| {
+            day.slots
+                .iter()
+                .enumerate()
+                .filter(|(_, slot)| slot.iter().any(|entry| entry.recipe_id == recipe.id))
+                .map(move |(slot_idx, _)| (day_idx, slot_idx))
+        })
+        .map(|(day_idx, slot_idx)| {
+            let slot_name = meal_planner
+                .get_meal_slots()
+                .get(slot_idx)
+                .cloned()
+                .unwrap_or_default();
+            format!("Day {} {}", day_idx + 1, slot_name)
+        })
+        .collect();
+    push_wrapped(
+        &mut lines,
+        Font::Regular,
+        10.,
+        0.,
+        &format!("Planned for {}", planned.join(", ")),
+    );
+    lines.push((Font::Regular, 10., 0., String::new()));
+
+    push_wrapped(&mut lines, Font::Bold, 12., 0., "Ingredients");
+    for ingredient in recipe.ingredients.lines().map(str::trim) {
+        if !ingredient.is_empty() {
+            push_wrapped(
+                &mut lines,
+                Font::Regular,
+                10.,
+                10.,
+                &format!("• {}", ingredient),
+            );
+        }
+    }
+    lines.push((Font::Regular, 10., 0., String::new()));
+
+    push_wrapped(&mut lines, Font::Bold, 12., 0., "Instructions");
+    for step in recipe.instructions.lines().map(str::trim) {
+        if !step.is_empty() {
+            push_wrapped(&mut lines, Font::Regular, 10., 0., step);
+            lines.push((Font::Regular, 4., 0., String::new()));
+        }
+    }
+    lines
+}
+
+/// One card per planned recipe, continuing on further pages when it's long.
+fn recipe_cards(document: &mut Document, meal_planner: &MealPlanner) {
+    let mut recipe_ids: Vec<Uuid> = vec![];
+    for entry in meal_planner
+        .get_daily_plan()
+        .iter()
+        .flat_map(|day| day.entries())
+    {
+        if !recipe_ids.contains(&entry.recipe_id) {
+            recipe_ids.push(entry.recipe_id);
+        }
+    }
+
+    let (page_width, page_height) = A4_PORTRAIT;
+    let width = page_width - 2. * CARD_MARGIN;
+    for recipe in recipe_ids
+        .iter()
+        .filter_map(|id| meal_planner.get_recipe_by_id(id))
+    {
+        let mut page = document.add_page(A4_PORTRAIT);
+        let mut y = CARD_MARGIN;
+        for (font, size, indent, text) in card_lines(meal_planner, recipe, width) {
+            let line_height = size * 1.3;
+            if y + line_height > page_height - CARD_MARGIN {
+                page = document.add_page(A4_PORTRAIT);
+                y = CARD_MARGIN;
+            }
+            y += line_height;
+            page.text(CARD_MARGIN + indent, y, size, font, &text);
+        }
+    }
+}
+
+/// The plan as a PDF: the week grids, then a card for every planned recipe.
+pub fn plan_pdf(meal_planner: &MealPlanner) -> Vec<u8> {
+    let mut document = Document::default();
+    week_pages(&mut document, meal_planner);
+    recipe_cards(&mut document, meal_planner);
+    document.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::PlanEntry;
+
+    fn page_count(pdf: &[u8]) -> usize {
+        let text = String::from_utf8_lossy(pdf);
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        let pages = text.matches("/Type /Page ").count();
+        assert!(text.contains(&format!("/Count {} ", pages)));
+        pages
+    }
+
+    fn lines(count: usize) -> Vec<String> {
+        (1..=count).map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn truncate_lines_marks_the_cut() {
+        assert_eq!(truncate_lines(lines(2), 3), lines(2));
+        assert_eq!(truncate_lines(lines(3), 3), lines(3));
+        assert_eq!(truncate_lines(lines(4), 3), vec!["1", "2", "3…"]);
+        assert_eq!(truncate_lines(lines(4), 0), vec!["1…"]);
+        assert_eq!(truncate_lines(vec![], 0), Vec::<String>::new());
+    }
+
+    #[test]
+    fn empty_plan_prints_one_week() {
+        let meal_planner = MealPlanner::default();
+        assert_eq!(page_count(&plan_pdf(&meal_planner)), 1);
+    }
+
+    #[test]
+    fn long_plans_print_a_page_per_week() {
+        let mut meal_planner = MealPlanner::default();
+        meal_planner.set_plan_length(DAYS_PER_PAGE + 3);
+        let recipe = meal_planner.create_draft_recipe().unwrap();
+        recipe.title = "Porridge".to_string();
+        let recipe_id = recipe.id;
+        for day in 0..meal_planner.plan_length() {
+            meal_planner.add_recipe_to_planner(day, 0, 0, PlanEntry::new(recipe_id));
+        }
+        assert_eq!(page_count(&plan_pdf(&meal_planner)), 2 + 1);
+    }
+
+    #[test]
+    fn long_recipes_continue_on_more_pages() {
+        let mut meal_planner = MealPlanner::default();
+        let recipe = meal_planner.create_draft_recipe().unwrap();
+        recipe.title = "Feast".to_string();
+        recipe.instructions = (1..=200)
+            .map(|step| format!("Step {}: stir the pot and keep stirring until done.", step))
+            .collect::<Vec<String>>()
+            .join("\n");
+        let recipe_id = recipe.id;
+        // More meals than fit in a cell, which only get one card.
+        for slot in 0..meal_planner.get_meal_slots().len() {
+            for _ in 0..20 {
+                meal_planner.add_recipe_to_planner(0, slot, 0, PlanEntry::new(recipe_id));
+            }
+        }
+        let pages = page_count(&plan_pdf(&meal_planner));
+        // Every step takes a line and a gap, about 40 steps to a card page.
+        assert!((1 + 5..=1 + 6).contains(&pages), "{} pages", pages);
+    }
+}
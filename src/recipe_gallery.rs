@@ -7,7 +7,8 @@ use uuid::Uuid;
 use crate::{
     meal_planner::MealPlanner,
     models::{AnalysisResponseView, Recipe},
-    planner::Location,
+    planner::DragPayload,
+    prices::Cost,
     recipe_title,
     util::{hb, percentage},
 };
@@ -30,7 +31,7 @@ impl<'a> Widget for Ingredients<'a> {
                     if ingredient.parsed.is_none() {
                         return;
                     }
-                    let detail = ingredient.parsed.as_ref().unwrap().get(0).unwrap();
+                    let detail = ingredient.parsed.as_ref().unwrap().first().unwrap();
                     ui.label(&detail.food);
 
                     let layout = Layout::right_to_left(egui::Align::Center);
@@ -77,6 +78,7 @@ pub struct GalleryItem<'a> {
     recipe: &'a Recipe,
     size: &'a (f32, f32),
     selected: bool,
+    cost_per_serving: Option<Cost>,
 }
 
 impl<'a> GalleryItem<'a> {
@@ -85,8 +87,15 @@ impl<'a> GalleryItem<'a> {
             recipe,
             size,
             selected,
+            cost_per_serving: None,
         }
     }
+
+    /// Shows what a serving costs, when any of the ingredients has a price.
+    pub fn cost_per_serving(mut self, cost: Cost) -> Self {
+        self.cost_per_serving = (cost.amount > 0.).then_some(cost);
+        self
+    }
 }
 
 impl<'a> Widget for GalleryItem<'a> {
@@ -143,12 +152,19 @@ impl<'a> Widget for GalleryItem<'a> {
                         ui.horizontal(|ui| {
                             ui.label(hb(&format!(
                                 "Calories: {}",
-                                &self.recipe.macros.calories / (self.recipe.servings as i32)
+                                self.recipe.macros.calories / (self.recipe.servings as i32)
                             )));
 
                             let layout = Layout::right_to_left(egui::Align::Center);
                             ui.with_layout(layout, |ui| {
                                 ui.label(hb(&format!("Servings: {}", &self.recipe.servings)));
+                                if let Some(cost) = &self.cost_per_serving {
+                                    let response =
+                                        ui.label(hb(&format!("{} / serving", cost.label())));
+                                    if !cost.is_complete() {
+                                        response.on_hover_text(cost.unpriced_text());
+                                    }
+                                }
                             });
                         });
                         ui.separator();
@@ -172,6 +188,8 @@ pub struct RecipeGallery {
     item_dragging: bool,
     drag_image: Option<Image<'static>>,
     show_details: bool,
+    /// Recipes picked with Ctrl/Shift-click, dragged to the planner together.
+    selection: Vec<Uuid>,
 }
 
 impl RecipeGallery {
@@ -209,7 +227,7 @@ impl RecipeGallery {
 
                 egui::CentralPanel::default().show_inside(ui, |ui| {
                     ScrollArea::vertical().show(ui, |ui| {
-                        if let None = self.current_recipe {
+                        if self.current_recipe.is_none() {
                             ui.label("No Recipe to display...");
                             return;
                         }
@@ -259,15 +277,43 @@ impl RecipeGallery {
                 if i.key_pressed(egui::Key::Escape) {
                     self.show_details = false;
                     self.current_recipe = None;
-                    self.nutrients_view = AnalysisResponseView::default();
+                    self.nutrients_view = AnalysisResponseView;
                 }
             });
         }
         edit_clicked
     }
 
+    /// Ctrl-click toggles a recipe in the selection, Shift-click extends the
+    /// selection up to the recipe in the order the recipes are shown.
+    fn select(&mut self, shown: &[Uuid], recipe_id: Uuid, extend: bool) {
+        let anchor = self
+            .selection
+            .last()
+            .and_then(|anchor| shown.iter().position(|id| id == anchor));
+        let clicked = shown.iter().position(|id| *id == recipe_id);
+        match (extend, anchor, clicked) {
+            (true, Some(anchor), Some(clicked)) => {
+                let range = anchor.min(clicked)..=anchor.max(clicked);
+                for id in &shown[range] {
+                    if !self.selection.contains(id) {
+                        self.selection.push(*id);
+                    }
+                }
+            }
+            _ => match self.selection.iter().position(|id| *id == recipe_id) {
+                Some(idx) => {
+                    self.selection.remove(idx);
+                }
+                None => self.selection.push(recipe_id),
+            },
+        }
+    }
+
     pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) -> Option<Uuid> {
         let mut edit_recipe = None;
+        self.selection
+            .retain(|id| meal_planner.get_recipe_by_id(id).is_some());
         ui.input(|input_state| {
             if input_state.key_pressed(egui::Key::Delete) && self.current_recipe.is_some() {
                 meal_planner.remove_recipe(&self.current_recipe.unwrap());
@@ -283,7 +329,7 @@ impl RecipeGallery {
                     ui.centered_and_justified(|ui| {
                         ui.add(
                             TextEdit::singleline(&mut self.search_query)
-                                .hint_text("Search recipes..."),
+                                .hint_text("Search recipes, ingredients, labels..."),
                         );
                     });
                 });
@@ -303,29 +349,35 @@ impl RecipeGallery {
                             meal_planner.search_recipe(&self.search_query)
                         };
                         let size = (item_width, item_height);
+                        let recipe_ids: Vec<Uuid> = recipes.iter().map(|r| r.id).collect();
 
                         for recipe in recipes {
-                            let payload = Location {
-                                col: 0,
-                                row: usize::MAX,
-                                recipe_id: uuid::Uuid::from(recipe.id),
-                            };
-
-                            let is_selected = match self.current_recipe {
-                                Some(id) => recipe.id == id,
-                                None => false,
-                            };
-                            let item_response =
-                                ui.add(GalleryItem::new(&size, recipe, is_selected));
+                            let is_selected = self.current_recipe == Some(recipe.id)
+                                || self.selection.contains(&recipe.id);
+                            let cost = meal_planner.cost_per_serving(recipe);
+                            let item_response = ui.add(
+                                GalleryItem::new(&size, recipe, is_selected).cost_per_serving(cost),
+                            );
                             if item_response.clicked() {
-                                self.current_recipe.replace(Uuid::from(recipe.id));
-                                self.show_details = true;
+                                let modifiers = ui.input(|i| i.modifiers);
+                                if modifiers.command || modifiers.shift {
+                                    self.select(&recipe_ids, recipe.id, modifiers.shift);
+                                } else {
+                                    self.selection = vec![recipe.id];
+                                    self.current_recipe.replace(recipe.id);
+                                    self.show_details = true;
+                                }
                             }
 
                             if item_response.drag_started() {
                                 self.item_dragging = true;
                                 self.drag_image
                                     .replace(Image::from_uri(recipe.image_url.clone()));
+                                let payload = if self.selection.contains(&recipe.id) {
+                                    DragPayload::Gallery(self.selection.clone())
+                                } else {
+                                    DragPayload::Gallery(vec![recipe.id])
+                                };
                                 item_response.dnd_set_drag_payload(payload);
                             }
 
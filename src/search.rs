@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{
+    food_names::{normalize, FoodNames},
+    models::Recipe,
+};
+
+/// How much a word counts by where it is found, so title matches rank first.
+const TITLE_WEIGHT: f32 = 4.;
+const INGREDIENT_WEIGHT: f32 = 3.;
+const LABEL_WEIGHT: f32 = 2.;
+const INSTRUCTIONS_WEIGHT: f32 = 1.;
+
+/// Shortest search term matched against the start or middle of longer words.
+const MIN_PARTIAL_LENGTH: usize = 3;
+
+/// Lowercase singular words of `text`, leaving out numbers and single letters.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 1 && word.chars().any(char::is_alphabetic))
+        .map(normalize)
+}
+
+/// Typos allowed in a search term: none in short words, where they would match
+/// too much.
+fn allowed_typos(term: &str) -> usize {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Edits turning `a` into `b`, counting swapped neighbours as one, or `None` when
+/// there are more than `max`.
+fn edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut before_previous: Vec<usize> = vec![];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (previous[j] + 1)
+                .min(row[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(before_previous[j - 2] + 1);
+            }
+        }
+        if row.iter().min().is_some_and(|min| *min > max) {
+            return None;
+        }
+        before_previous = std::mem::replace(&mut previous, row);
+    }
+    let distance = previous[b.len()];
+    (distance <= max).then_some(distance)
+}
+
+/// How well a word of a recipe matches a search term, from 0 to 1.
+fn match_quality(term: &str, word: &str) -> Option<f32> {
+    if term == word {
+        return Some(1.);
+    }
+    if term.chars().count() >= MIN_PARTIAL_LENGTH {
+        if word.starts_with(term) {
+            return Some(0.8);
+        }
+        if word.contains(term) {
+            return Some(0.5);
+        }
+    }
+    match edit_distance(term, word, allowed_typos(term))? {
+        1 => Some(0.7),
+        _ => Some(0.5),
+    }
+}
+
+/// Words of every recipe, for searching them by title, ingredients, instructions,
+/// diet and health labels and cuisine, meal and dish types, with typos. Reset it
+/// when the recipes or the food names change.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    /// Whether the words have been collected since the index was reset.
+    built: bool,
+    /// Recipes a word is found in, with the weight of where it is found.
+    words: HashMap<String, HashMap<Uuid, f32>>,
+    /// The last query and its results, as the search box asks again every frame.
+    last_search: Option<(String, Vec<Uuid>)>,
+}
+
+impl SearchIndex {
+    /// Collects the words of `recipes` unless the index was built already.
+    pub fn build<'a>(&mut self, recipes: impl Iterator<Item = &'a Recipe>, names: &FoodNames) {
+        if self.built {
+            return;
+        }
+        self.built = true;
+        self.words.clear();
+        self.last_search = None;
+        for recipe in recipes {
+            self.add_recipe(recipe, names);
+        }
+    }
+
+    fn add_text(&mut self, recipe_id: Uuid, text: &str, weight: f32) {
+        for word in words(text) {
+            let found = self.words.entry(word).or_default();
+            let best = found.entry(recipe_id).or_insert(weight);
+            *best = best.max(weight);
+        }
+    }
+
+    fn add_recipe(&mut self, recipe: &Recipe, names: &FoodNames) {
+        let id = recipe.id;
+        self.add_text(id, &recipe.title, TITLE_WEIGHT);
+        self.add_text(id, &recipe.ingredients, INGREDIENT_WEIGHT);
+        let macros = &recipe.macros;
+        for parsed in macros
+            .ingredients
+            .iter()
+            .flat_map(|ingr| ingr.parsed.iter().flatten())
+        {
+            self.add_text(id, &parsed.food, INGREDIENT_WEIGHT);
+            self.add_text(id, &names.key(&parsed.food), INGREDIENT_WEIGHT);
+        }
+        let labels = [
+            &macros.dietLabels,
+            &macros.healthLabels,
+            &macros.cuisineType,
+            &macros.mealType,
+            &macros.dishType,
+        ];
+        for label in labels.into_iter().flatten() {
+            self.add_text(id, label, LABEL_WEIGHT);
+        }
+        self.add_text(id, &recipe.instructions, INSTRUCTIONS_WEIGHT);
+    }
+
+    /// Score of each recipe for one search term: the best match among its words.
+    fn term_scores(&self, term: &str) -> HashMap<Uuid, f32> {
+        let mut scores: HashMap<Uuid, f32> = HashMap::new();
+        for (word, recipes) in &self.words {
+            let Some(quality) = match_quality(term, word) else {
+                continue;
+            };
+            for (id, weight) in recipes {
+                let score = scores.entry(*id).or_default();
+                *score = score.max(quality * weight);
+            }
+        }
+        scores
+    }
+
+    /// Number of search terms each recipe matches and its score for them.
+    fn query_scores(&self, query: &str) -> HashMap<Uuid, (usize, f32)> {
+        let mut terms: Vec<String> = words(query).collect();
+        terms.sort();
+        terms.dedup();
+        let mut results: HashMap<Uuid, (usize, f32)> = HashMap::new();
+        for term in terms {
+            for (id, score) in self.term_scores(&term) {
+                let (matched, total) = results.entry(id).or_default();
+                *matched += 1;
+                *total += score;
+            }
+        }
+        results
+    }
+
+    /// Recipes matching `query`, best first: those matching the most search terms,
+    /// then the closest matches. A query naming a food also finds its synonyms.
+    pub fn search(&mut self, query: &str, names: &FoodNames) -> &[Uuid] {
+        if self
+            .last_search
+            .as_ref()
+            .is_none_or(|(last_query, _)| last_query != query)
+        {
+            let results = self.rank(query, names);
+            self.last_search = Some((query.to_string(), results));
+        }
+        self.last_search
+            .as_ref()
+            .map_or(&[], |(_, results)| results.as_slice())
+    }
+
+    fn rank(&self, query: &str, names: &FoodNames) -> Vec<Uuid> {
+        let mut results = self.query_scores(query);
+        let synonym = names.key(query);
+        if synonym != normalize(query) {
+            for (id, found) in self.query_scores(&synonym) {
+                let best = results.entry(id).or_default();
+                if found > *best {
+                    *best = found;
+                }
+            }
+        }
+        let mut ranked: Vec<(Uuid, (usize, f32))> = results.into_iter().collect();
+        ranked.sort_by(
+            |(a_id, (a_matched, a_score)), (b_id, (b_matched, b_score))| {
+                b_matched
+                    .cmp(a_matched)
+                    .then(b_score.total_cmp(a_score))
+                    .then(a_id.cmp(b_id))
+            },
+        );
+        ranked.into_iter().map(|(id, _)| id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meal_planner::MealPlanner;
+
+    fn recipe(title: &str, ingredients: &str, instructions: &str) -> Recipe {
+        Recipe {
+            title: title.to_string(),
+            ingredients: ingredients.to_string(),
+            instructions: instructions.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn edit_distance_counts_swaps_once() {
+        assert_eq!(edit_distance("tomato", "tomato", 0), Some(0));
+        assert_eq!(edit_distance("tomatoe", "tomato", 1), Some(1));
+        assert_eq!(edit_distance("recieve", "receive", 1), Some(1));
+        assert_eq!(edit_distance("kitten", "sitting", 3), Some(3));
+        assert_eq!(edit_distance("", "ab", 2), Some(2));
+    }
+
+    #[test]
+    fn edit_distance_stops_past_max() {
+        assert_eq!(edit_distance("kitten", "sitting", 2), None);
+        assert_eq!(edit_distance("pie", "pineapple", 2), None);
+        assert_eq!(edit_distance("crème", "creme", 0), None);
+    }
+
+    #[test]
+    fn match_quality_counts_characters() {
+        assert_eq!(match_quality("tomato", "tomato"), Some(1.));
+        assert_eq!(match_quality("tom", "tomato"), Some(0.8));
+        assert_eq!(match_quality("mat", "tomato"), Some(0.5));
+        assert_eq!(match_quality("tomatoe", "tomato"), Some(0.7));
+        assert_eq!(match_quality("to", "tomato"), None);
+        assert_eq!(match_quality("jü", "jüs"), None);
+        assert_eq!(match_quality("crè", "crème"), Some(0.8));
+    }
+
+    #[test]
+    fn search_ranks_title_matches_and_more_terms_first() {
+        let soup = recipe("Tomato soup", "4 tomatoes, 1 onion", "Simmer.");
+        let pasta = recipe("Pasta", "200 g pasta", "Top with tomato sauce.");
+        let salad = recipe("Green salad", "1 lettuce", "Serve with soup.");
+        let names = FoodNames::default();
+        let mut index = SearchIndex::default();
+        index.build([&pasta, &salad, &soup].into_iter(), &names);
+
+        assert_eq!(index.search("tomatoes", &names), [soup.id, pasta.id]);
+        assert_eq!(index.search("tomato soup", &names)[0], soup.id);
+        assert_eq!(index.search("tomatoe", &names), [soup.id, pasta.id]);
+        assert_eq!(index.search("lettuse", &names), [salad.id]);
+        assert!(index.search("zz", &names).is_empty());
+    }
+
+    #[test]
+    fn search_finds_synonyms() {
+        let ratatouille = recipe("Ratatouille", "2 zucchini, 1 eggplant", "");
+        let names = FoodNames::default();
+        let mut index = SearchIndex::default();
+        index.build([&ratatouille].into_iter(), &names);
+        assert_eq!(index.search("Courgettes", &names), [ratatouille.id]);
+        assert_eq!(index.search("aubergine", &names), [ratatouille.id]);
+    }
+
+    #[test]
+    fn search_follows_recipe_and_name_changes() {
+        let mut meal_planner = MealPlanner::default();
+        let recipe_id = meal_planner.create_draft_recipe().unwrap().id;
+        meal_planner.edit_recipe(&recipe_id, |recipe| {
+            recipe.title = "Kohlrabi slaw".to_string()
+        });
+        assert_eq!(meal_planner.search_recipe("kohl").len(), 1);
+        assert!(meal_planner.search_recipe("turnip cabbage").is_empty());
+
+        meal_planner.add_food_name("turnip cabbage", "kohlrabi");
+        assert_eq!(meal_planner.search_recipe("turnip cabbage").len(), 1);
+
+        meal_planner.edit_recipe(&recipe_id, |recipe| {
+            recipe.title = "Noodle salad".to_string()
+        });
+        assert!(meal_planner.search_recipe("kohl").is_empty());
+        assert_eq!(meal_planner.search_recipe("noodles").len(), 1);
+
+        meal_planner.remove_recipe(&recipe_id);
+        assert!(meal_planner.search_recipe("noodles").is_empty());
+    }
+}
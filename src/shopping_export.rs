@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{meal_planner::MealPlanner, shopping_list::ShoppingItem, units::format_amount};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    #[default]
+    Text,
+    Markdown,
+    Csv,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [
+        ExportFormat::Text,
+        ExportFormat::Markdown,
+        ExportFormat::Csv,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Text => "Plain text",
+            ExportFormat::Markdown => "Markdown checklist",
+            ExportFormat::Csv => "CSV",
+        }
+    }
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Text => "shopping-list.txt",
+            ExportFormat::Markdown => "shopping-list.md",
+            ExportFormat::Csv => "shopping-list.csv",
+        }
+    }
+}
+
+fn item_line(item: &ShoppingItem) -> String {
+    let mut line = match item.weight() {
+        Some(weight) => format!("{}: {} ({})", item.food, item.quantity, weight),
+        None => format!("{}: {}", item.food, item.quantity),
+    };
+    if let Some(packing) = item.packing_text() {
+        line.push_str(&format!(", buy {}", packing));
+    }
+    line
+}
+
+/// Quotes a CSV field when it needs it.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// The shopping list as text to paste or save. `items` come grouped by aisle, in the
+/// order of the store.
+pub fn export(items: &[ShoppingItem], format: ExportFormat, meal_planner: &MealPlanner) -> String {
+    let mut lines = vec![];
+    let mut aisle = None;
+    match format {
+        ExportFormat::Text => {
+            lines.push("Shopping list".to_string());
+            for item in items {
+                if aisle != Some(&item.aisle) {
+                    lines.push(String::new());
+                    lines.push(item.aisle.to_uppercase());
+                    aisle = Some(&item.aisle);
+                }
+                lines.push(format!("- {}", item_line(item)));
+            }
+        }
+        ExportFormat::Markdown => {
+            lines.push("# Shopping list".to_string());
+            for item in items {
+                if aisle != Some(&item.aisle) {
+                    lines.push(String::new());
+                    lines.push(format!("## {}", item.aisle));
+                    lines.push(String::new());
+                    aisle = Some(&item.aisle);
+                }
+                let mark = if item.is_checked(meal_planner) {
+                    "x"
+                } else {
+                    " "
+                };
+                lines.push(format!("- [{}] {}", mark, item_line(item)));
+            }
+        }
+        ExportFormat::Csv => {
+            lines.push("Aisle,Ingredient,Quantity,Unit,Weight (g),Packages,Checked".to_string());
+            for item in items {
+                lines.push(
+                    [
+                        csv_field(&item.aisle),
+                        csv_field(&item.food),
+                        format_amount(item.quantity.amount),
+                        csv_field(&item.quantity.unit.symbol(item.quantity.amount)),
+                        if item.grams > 0. {
+                            format_amount(item.grams.round())
+                        } else {
+                            String::new()
+                        },
+                        csv_field(
+                            &item
+                                .packing
+                                .as_ref()
+                                .map_or(String::new(), |packing| packing.to_string()),
+                        ),
+                        item.is_checked(meal_planner).to_string(),
+                    ]
+                    .join(","),
+                );
+            }
+        }
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        aisles::{DAIRY_EGGS, PRODUCE},
+        units::{Quantity, Unit},
+    };
+
+    fn item(aisle: &str, food: &str, quantity: Quantity, grams: f32) -> ShoppingItem {
+        ShoppingItem {
+            key: food.to_lowercase(),
+            food: food.to_string(),
+            aisle: aisle.to_string(),
+            quantity,
+            grams,
+            in_pantry: None,
+            packing: None,
+        }
+    }
+
+    /// Tomatoes, ticked off, and limes, both in produce, then milk.
+    fn items(meal_planner: &mut MealPlanner) -> Vec<ShoppingItem> {
+        meal_planner.set_shopping_checked("tomatoes", Some(400.));
+        vec![
+            item(PRODUCE, "Tomatoes", Quantity::grams(400.), 400.),
+            item(PRODUCE, "Limes", Quantity::new(3., Unit::parse("")), 0.),
+            item(DAIRY_EGGS, "Milk", Quantity::new(1., Unit::Litre), 1030.),
+        ]
+    }
+
+    #[test]
+    fn text_groups_items_by_aisle() {
+        let mut meal_planner = MealPlanner::default();
+        let items = items(&mut meal_planner);
+        assert_eq!(
+            export(&items, ExportFormat::Text, &meal_planner),
+            "Shopping list\n\nPRODUCE\n- Tomatoes: 400g (400g)\n- Limes: 3\n\n\
+             DAIRY & EGGS\n- Milk: 1l (1030g)\n"
+        );
+    }
+
+    #[test]
+    fn markdown_ticks_checked_items() {
+        let mut meal_planner = MealPlanner::default();
+        let items = items(&mut meal_planner);
+        assert_eq!(
+            export(&items, ExportFormat::Markdown, &meal_planner),
+            "# Shopping list\n\n## Produce\n\n- [x] Tomatoes: 400g (400g)\n- [ ] Limes: 3\n\n\
+             ## Dairy & eggs\n\n- [ ] Milk: 1l (1030g)\n"
+        );
+    }
+
+    #[test]
+    fn csv_has_a_row_per_item() {
+        let mut meal_planner = MealPlanner::default();
+        let items = items(&mut meal_planner);
+        assert_eq!(
+            export(&items, ExportFormat::Csv, &meal_planner),
+            "Aisle,Ingredient,Quantity,Unit,Weight (g),Packages,Checked\n\
+             Produce,Tomatoes,400,g,400,,true\n\
+             Produce,Limes,3,,,,false\n\
+             Dairy & eggs,Milk,1,l,1030,,false\n"
+        );
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("salt, flaky"), "\"salt, flaky\"");
+        assert_eq!(csv_field("12\" pizza"), "\"12\"\" pizza\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("carriage\rreturn"), "\"carriage\rreturn\"");
+
+        let meal_planner = MealPlanner::default();
+        let odd = item("Odd, \"aisle\"", "a\nb", Quantity::grams(1.), 1.);
+        let csv = export(&[odd], ExportFormat::Csv, &meal_planner);
+        assert_eq!(
+            csv.lines().skip(1).collect::<Vec<&str>>().join("\n"),
+            "\"Odd, \"\"aisle\"\"\",\"a\nb\",1,g,1,,false"
+        );
+    }
+}
@@ -1,55 +1,549 @@
 use std::collections::HashMap;
 
+use crate::{
+    aisles::{StoreLayout, OTHER},
+    food_names::FoodNames,
+    leftovers::batch_usage,
+    meal_planner::MealPlanner,
+    models::ParsedNutrient,
+    packages::{PackageSize, PackageSizesView, Packing},
+    pantry::PantryItem,
+    plan::DayPlan,
+    shopping_export::{export, ExportFormat},
+    typography::icons::{
+        ICON_CLIPBOARD_COPY, ICON_DOWNLOAD, ICON_LIST_RESTART, ICON_PACKAGE_PLUS, ICON_PLUS,
+        ICON_ROTATE_CCW, ICON_TRASH_2,
+    },
+    units::{format_amount, Quantity, QuantitySum, Unit},
+    util::{day_range_ui, hb, hs, save_file},
+};
+use egui::{Button, CollapsingHeader, ComboBox, DragValue, Grid, RichText};
 use egui_extras::{Column, TableBuilder};
 use uuid::Uuid;
 
-use crate::meal_planner::MealPlanner;
+/// One line of the shopping list: a food with everything the plan needs of it.
+#[derive(Debug, Clone)]
+pub struct ShoppingItem {
+    /// Identifies the item across plan edits: the food name as the dictionary
+    /// normalizes it.
+    pub key: String,
+    pub food: String,
+    pub aisle: String,
+    /// What is left to buy, in the unit the recipes mostly use.
+    pub quantity: Quantity,
+    pub grams: f32,
+    /// Part of the total already in the pantry.
+    pub in_pantry: Option<Quantity>,
+    /// The packages to buy, for foods with package sizes.
+    pub packing: Option<Packing>,
+}
+
+impl ShoppingItem {
+    /// `have` in the unit of this item. Goes through the weight when the units
+    /// measure different things, using what the recipes say the item weighs.
+    fn amount_of(&self, have: &Quantity) -> Option<f32> {
+        if let Some(same) = have.convert(&self.quantity.unit) {
+            return Some(same.amount);
+        }
+        let grams_per_unit = self.grams / self.quantity.amount;
+        let grams = have.convert(&Unit::Gram)?.amount;
+        grams_per_unit.is_normal().then(|| grams / grams_per_unit)
+    }
+
+    /// Takes what the pantry already has off the quantity to buy.
+    fn subtract_pantry(&mut self, pantry: &[PantryItem], names: &FoodNames) {
+        let needed = self.quantity.amount;
+        let have: f32 = pantry
+            .iter()
+            .filter(|item| item.is_food(&self.food, names))
+            .filter_map(|item| self.amount_of(&item.quantity))
+            .sum();
+        if have <= 0. || needed <= 0. {
+            return;
+        }
+        let covered = have.min(needed);
+        self.grams *= 1. - covered / needed;
+        self.in_pantry = Some(Quantity::new(covered, self.quantity.unit.clone()).simplified());
+        self.quantity = Quantity::new(needed - covered, self.quantity.unit.clone()).simplified();
+    }
+
+    /// Adds an extra item of the same food to this one, if their units go together.
+    fn merge_extra(&mut self, extra: &ExtraItem, names: &FoodNames) -> bool {
+        if !names.same_food(&self.food, &extra.name) {
+            return false;
+        }
+        let Some(amount) = self.amount_of(&extra.quantity) else {
+            return false;
+        };
+        let grams_per_unit = self.grams / self.quantity.amount;
+        if grams_per_unit.is_finite() {
+            self.grams += amount * grams_per_unit;
+        }
+        self.quantity.amount += amount;
+        true
+    }
+
+    /// Rounds what is left to buy up to whole packages of the sizes the food comes in.
+    fn round_to_packages(&mut self, sizes: &[PackageSize], names: &FoodNames) {
+        let sizes: Vec<(f32, Quantity)> = sizes
+            .iter()
+            .filter(|size| size.is_food(&self.food, names))
+            .filter_map(|size| Some((self.amount_of(&size.quantity)?, size.quantity.clone())))
+            .collect();
+        if !sizes.is_empty() {
+            self.packing = Packing::round_up(&self.quantity, &sizes);
+        }
+    }
+
+    /// What ends up bought: whole packages when the food comes in them.
+    pub fn bought(&self) -> Quantity {
+        self.packing.as_ref().map_or_else(
+            || self.quantity.clone(),
+            |packing| packing.total.simplified(),
+        )
+    }
+
+    /// The packages to buy and what they leave over.
+    pub fn packing_text(&self) -> Option<String> {
+        let packing = self.packing.as_ref()?;
+        if packing.has_leftover() {
+            Some(format!("{}, {} left over", packing, packing.leftover))
+        } else {
+            Some(packing.to_string())
+        }
+    }
+
+    /// The weight to buy, unless it is unknown.
+    pub fn weight(&self) -> Option<String> {
+        (self.grams > 0.).then(|| format!("{}g", format_amount(self.grams.round())))
+    }
+
+    /// Whether the pantry has all of it.
+    pub fn is_covered(&self) -> bool {
+        self.quantity.amount <= 0.
+    }
+
+    /// What a check is stamped with, so the item unchecks when it changes: the
+    /// weight, or the quantity for extra items with no known weight.
+    fn check_amount(&self) -> f32 {
+        if self.grams > 0. {
+            self.grams
+        } else {
+            self.quantity.amount
+        }
+    }
+
+    pub fn is_checked(&self, meal_planner: &MealPlanner) -> bool {
+        meal_planner.is_shopping_checked(&self.key, self.check_amount())
+    }
+
+    fn set_checked(&self, meal_planner: &mut MealPlanner, checked: bool) {
+        meal_planner.set_shopping_checked(&self.key, checked.then(|| self.check_amount()));
+    }
+}
+
+/// Something to buy that isn't in any recipe, like washing-up liquid.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExtraItem {
+    pub id: Uuid,
+    pub name: String,
+    pub quantity: Quantity,
+    pub aisle: String,
+}
+
+impl ExtraItem {
+    /// Shopping list key of the extra item `id`.
+    pub fn key(id: &Uuid) -> String {
+        format!("extra:{}", id)
+    }
+
+    fn to_item(&self, layout: &StoreLayout) -> ShoppingItem {
+        let aisle = if layout.is_moved(&self.name) {
+            layout.aisle_for(&self.name, None)
+        } else {
+            self.aisle.clone()
+        };
+        ShoppingItem {
+            key: Self::key(&self.id),
+            food: self.name.clone(),
+            aisle,
+            quantity: self.quantity.simplified(),
+            grams: self
+                .quantity
+                .convert(&Unit::Gram)
+                .map_or(0., |grams| grams.amount),
+            in_pantry: None,
+            packing: None,
+        }
+    }
+}
+
+/// The extra item being written.
+#[derive(Debug, Clone)]
+struct ExtraForm {
+    name: String,
+    amount: f32,
+    unit: String,
+    aisle: String,
+}
+
+impl Default for ExtraForm {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            amount: 1.,
+            unit: String::new(),
+            aisle: OTHER.to_string(),
+        }
+    }
+}
+
+/// Moves a shopping list item to the pantry.
+fn mark_bought(meal_planner: &mut MealPlanner, item: &ShoppingItem) {
+    meal_planner.add_to_pantry(PantryItem::new(&item.food, item.bought(), None));
+    item.set_checked(meal_planner, false);
+}
+
+/// The quantity of a parsed ingredient as written in the recipe, falling back on
+/// its weight when Edamam found no measure.
+pub fn parsed_quantity(model: &ParsedNutrient) -> Quantity {
+    match &model.measure {
+        Some(measure) if model.quantity > 0. => Quantity::new(model.quantity, Unit::parse(measure)),
+        _ => Quantity::grams(model.weight),
+    }
+}
 
 #[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct ShoppingList {}
+#[serde(default)]
+pub struct ShoppingList {
+    layout: StoreLayout,
+    hide_checked: bool,
+    export_format: ExportFormat,
+    /// Plan days to shop for, as a from..=to range. None for the whole plan.
+    days: Option<(usize, usize)>,
+    /// Meal slots left out of the list, by name.
+    skipped_slots: Vec<String>,
+    #[serde(skip)]
+    extra_form: ExtraForm,
+    #[serde(skip)]
+    package_sizes: PackageSizesView,
+}
 
 impl ShoppingList {
-    fn shopping_list(&self, plan: &[Vec<Uuid>], meal_planner: &MealPlanner) -> Vec<(String, f32)> {
-        let mut list = HashMap::new();
-        plan.iter().for_each(|day| {
-            for r_id in day {
-                let recipe = meal_planner.get_recipe_by_id(r_id).unwrap();
-                for ingr in &recipe.macros.ingredients {
-                    if ingr.parsed.is_none() {
-                        continue;
-                    }
-                    let model = ingr.parsed.as_ref().unwrap();
+    /// Whether the meals of a plan slot go on the list.
+    fn includes(&self, meal_planner: &MealPlanner, day_idx: usize, slot_idx: usize) -> bool {
+        let in_days = self
+            .days
+            .is_none_or(|(from, to)| (from..=to).contains(&day_idx));
+        let slot = meal_planner.get_meal_slots().get(slot_idx);
+        in_days && !slot.is_some_and(|slot| self.skipped_slots.contains(slot))
+    }
 
-                    if model.is_empty() {
-                        continue;
+    fn range_ui(&mut self, ui: &mut egui::Ui, meal_planner: &MealPlanner) {
+        let plan_length = meal_planner.plan_length();
+        ui.horizontal(|ui| {
+            let mut whole_plan = self.days.is_none();
+            if ui.checkbox(&mut whole_plan, "Whole plan").changed() {
+                self.days = (!whole_plan).then(|| (0, plan_length.saturating_sub(1)));
+            }
+            if let Some((from, to)) = self.days.as_mut() {
+                day_range_ui(ui, from, to, plan_length);
+                ui.label(hs(&format!(
+                    "{} – {}",
+                    meal_planner.plan_day_date(*from).format("%a %-d %b"),
+                    meal_planner.plan_day_date(*to).format("%a %-d %b")
+                )));
+            }
+        });
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Meals");
+            for slot in meal_planner.get_meal_slots() {
+                let mut included = !self.skipped_slots.contains(slot);
+                if ui.checkbox(&mut included, slot.as_str()).changed() {
+                    if included {
+                        self.skipped_slots.retain(|skipped| skipped != slot);
+                    } else {
+                        self.skipped_slots.push(slot.clone());
                     }
+                }
+            }
+        });
+    }
 
-                    let model = model.first().unwrap();
-                    if !list.contains_key(&model.foodId) {
-                        list.insert(model.foodId.clone(), (model.food.clone(), 0.0));
-                    }
-                    let value = list.get_mut(&model.foodId).unwrap();
-                    value.1 += model.weight / recipe.servings as f32;
+    fn shopping_list(&self, plan: &[DayPlan], meal_planner: &MealPlanner) -> Vec<ShoppingItem> {
+        let mut list: HashMap<String, (String, Option<String>, QuantitySum)> = HashMap::new();
+        let usage = batch_usage(plan, meal_planner);
+        let names = meal_planner.get_food_names();
+        let entries = plan.iter().enumerate().flat_map(|(day_idx, day)| {
+            day.slots
+                .iter()
+                .enumerate()
+                .flat_map(move |(slot_idx, slot)| {
+                    slot.iter()
+                        .enumerate()
+                        .map(move |(row_idx, entry)| ((day_idx, slot_idx, row_idx), entry))
+                })
+        });
+        entries.for_each(|(location, entry)| {
+            let (day_idx, slot_idx, _) = location;
+            if !self.includes(meal_planner, day_idx, slot_idx) {
+                return;
+            }
+            let recipe = meal_planner.get_recipe_by_id(&entry.recipe_id).unwrap();
+            let batch = entry
+                .batch
+                .and_then(|id| Some((usage.get(&id)?, meal_planner.get_batch(&id)?)));
+            // Batch cooked meals are bought for once, with the meal they are cooked for.
+            let servings = match batch {
+                Some((usage, _)) if usage.cook_location() != Some(location) => return,
+                Some((_, batch)) => batch.servings,
+                None => meal_planner.entry_servings(entry, recipe),
+            };
+            // A line like "salt and pepper" is parsed into several foods.
+            let models = recipe
+                .macros
+                .ingredients
+                .iter()
+                .flat_map(|ingr| ingr.parsed.iter().flatten());
+            for model in models {
+                let scale = servings / recipe.servings as f32;
+                let mut quantity = parsed_quantity(model);
+                quantity.amount *= scale;
+                let (_, category, sum) = list.entry(names.key(&model.food)).or_insert_with(|| {
+                    (
+                        names.display_name(&model.food),
+                        None,
+                        QuantitySum::default(),
+                    )
+                });
+                if category.is_none() {
+                    category.clone_from(&model.foodCategory);
                 }
+                sum.add(quantity, model.weight * scale);
+            }
+        });
+
+        let mut items = list
+            .into_iter()
+            .map(|(key, (food, category, sum))| ShoppingItem {
+                key,
+                aisle: self.layout.aisle_for(&food, category.as_deref()),
+                food,
+                quantity: sum.total().simplified(),
+                grams: sum.grams(),
+                in_pantry: None,
+                packing: None,
+            })
+            .collect::<Vec<ShoppingItem>>();
+        for extra in meal_planner.get_shopping_extras() {
+            if !items.iter_mut().any(|item| item.merge_extra(extra, names)) {
+                items.push(extra.to_item(&self.layout));
             }
+        }
+        for item in items.iter_mut() {
+            item.subtract_pantry(meal_planner.get_pantry(), names);
+            item.round_to_packages(meal_planner.get_package_sizes(), names);
+        }
+        // In the order of the store, then alphabetically within an aisle.
+        items.sort_by_cached_key(|item| {
+            (
+                self.layout.position(&item.aisle),
+                item.aisle.clone(),
+                item.food.to_lowercase(),
+            )
         });
+        items
+    }
 
-        let mut ids = list.keys().collect::<Vec<&String>>();
-        ids.sort_unstable();
-        ids.iter()
-            .map(|id| list.get(id.as_str()).unwrap().clone())
-            .collect::<Vec<(String, f32)>>()
+    fn aisle_ui(&mut self, ui: &mut egui::Ui, item: &ShoppingItem) {
+        let mut aisle = item.aisle.clone();
+        ComboBox::from_id_salt(("shopping_aisle", &item.key))
+            .selected_text(&aisle)
+            .show_ui(ui, |ui| {
+                for option in &self.layout.aisles {
+                    ui.selectable_value(&mut aisle, option.clone(), option);
+                }
+            });
+        if aisle != item.aisle {
+            self.layout.set_aisle(&item.food, Some(aisle));
+        }
+        if self.layout.is_moved(&item.food)
+            && ui
+                .small_button(ICON_ROTATE_CCW)
+                .on_hover_text("Back to its usual aisle")
+                .clicked()
+        {
+            self.layout.set_aisle(&item.food, None);
+        }
     }
 
-    pub fn show(&self, ui: &mut egui::Ui, meal_planner: &MealPlanner) {
+    fn extras_ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        ui.label(hs(
+            "Things to buy that aren't in any recipe. They stay on the list until removed.",
+        ));
+        let mut remove = None;
+        Grid::new("shopping_extras")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                for extra in meal_planner.get_shopping_extras() {
+                    ui.label(&extra.name);
+                    ui.label(extra.quantity.to_string());
+                    ui.label(hs(&extra.aisle));
+                    if ui
+                        .button(ICON_TRASH_2)
+                        .on_hover_text("Remove from the list")
+                        .clicked()
+                    {
+                        remove = Some(extra.id);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(id) = remove {
+            meal_planner.remove_shopping_extra(&id);
+        }
+
+        let form = &mut self.extra_form;
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut form.name)
+                    .hint_text("Item")
+                    .desired_width(120.),
+            );
+            ui.add(
+                DragValue::new(&mut form.amount)
+                    .range(0.0..=100000.)
+                    .speed(0.5),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut form.unit)
+                    .hint_text("unit")
+                    .desired_width(50.),
+            );
+            ComboBox::from_id_salt("shopping_extra_aisle")
+                .selected_text(&form.aisle)
+                .show_ui(ui, |ui| {
+                    for aisle in &self.layout.aisles {
+                        ui.selectable_value(&mut form.aisle, aisle.clone(), aisle);
+                    }
+                });
+            let name = form.name.trim();
+            if ui
+                .add_enabled(
+                    !name.is_empty() && form.amount > 0.,
+                    Button::new(format!("{} Add", ICON_PLUS)),
+                )
+                .clicked()
+            {
+                meal_planner.add_shopping_extra(ExtraItem {
+                    id: Uuid::new_v4(),
+                    name: name.to_string(),
+                    quantity: Quantity::new(form.amount, Unit::parse(&form.unit)),
+                    aisle: form.aisle.clone(),
+                });
+                form.name.clear();
+            }
+        });
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        self.range_ui(ui, meal_planner);
+        ui.separator();
+
         let plan = &meal_planner.get_daily_plan();
-        let list = self.shopping_list(plan, meal_planner);
+        let mut list = self.shopping_list(plan, meal_planner);
+        let covered = list.iter().filter(|item| item.is_covered()).count();
+        list.retain(|item| !item.is_covered());
+
+        CollapsingHeader::new("Store layout")
+            .id_salt("shopping_store_layout")
+            .show(ui, |ui| self.layout.ui(ui));
+
+        CollapsingHeader::new("Extra items")
+            .id_salt("shopping_extra_items")
+            .show(ui, |ui| self.extras_ui(ui, meal_planner));
+
+        CollapsingHeader::new("Package sizes")
+            .id_salt("shopping_package_sizes")
+            .show(ui, |ui| self.package_sizes.ui(ui, meal_planner));
+
+        let checked = list
+            .iter()
+            .filter(|item| item.is_checked(meal_planner))
+            .count();
+        ui.horizontal(|ui| {
+            ui.label(hs(&format!("{} of {} items checked", checked, list.len())));
+            ui.checkbox(&mut self.hide_checked, "Hide checked");
+            if ui
+                .add_enabled(
+                    checked > 0,
+                    Button::new(format!("{} Reset", ICON_LIST_RESTART)),
+                )
+                .on_hover_text("Uncheck every item")
+                .clicked()
+            {
+                meal_planner.reset_shopping_checks();
+            }
+            if ui
+                .add_enabled(
+                    checked > 0,
+                    Button::new(format!("{} Checked to pantry", ICON_PACKAGE_PLUS)),
+                )
+                .on_hover_text("Mark the checked items as bought")
+                .clicked()
+            {
+                for item in &list {
+                    if item.is_checked(meal_planner) {
+                        mark_bought(meal_planner, item);
+                    }
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ComboBox::from_id_salt("shopping_export_format")
+                .selected_text(self.export_format.label())
+                .show_ui(ui, |ui| {
+                    for format in ExportFormat::ALL {
+                        ui.selectable_value(&mut self.export_format, format, format.label());
+                    }
+                });
+            if ui
+                .button(format!("{} Copy", ICON_CLIPBOARD_COPY))
+                .on_hover_text("Copy the list to the clipboard")
+                .clicked()
+            {
+                ui.ctx()
+                    .copy_text(export(&list, self.export_format, meal_planner));
+            }
+            if ui
+                .button(format!("{} Save", ICON_DOWNLOAD))
+                .on_hover_text("Save the list to a file")
+                .clicked()
+            {
+                let text = export(&list, self.export_format, meal_planner);
+                save_file(self.export_format.file_name(), text.into_bytes());
+            }
+        });
+        if covered > 0 {
+            ui.label(hs(&format!(
+                "{} item{} already in the pantry.",
+                covered,
+                if covered == 1 { " is" } else { "s are" }
+            )));
+        }
+        if self.hide_checked {
+            list.retain(|item| !item.is_checked(meal_planner));
+        }
 
         TableBuilder::new(ui)
             .striped(true)
             .resizable(true)
             .column(Column::remainder())
             .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::auto())
             .header(30., |mut header| {
                 header.col(|ui| {
                     ui.heading("Ingredient");
@@ -57,18 +551,244 @@ impl ShoppingList {
                 header.col(|ui| {
                     ui.heading("Quantity");
                 });
+                header.col(|ui| {
+                    ui.heading("Weight");
+                });
+                header.col(|ui| {
+                    ui.heading("Aisle");
+                });
+                header.col(|_| {});
             })
             .body(|mut body| {
-                for (name, weight) in list {
+                let mut current_aisle = None;
+                for item in list {
+                    if current_aisle.as_ref() != Some(&item.aisle) {
+                        body.row(26., |mut row| {
+                            row.col(|ui| {
+                                ui.label(hb(&item.aisle));
+                            });
+                            row.col(|_| {});
+                            row.col(|_| {});
+                            row.col(|_| {});
+                            row.col(|_| {});
+                        });
+                        current_aisle = Some(item.aisle.clone());
+                    }
                     body.row(20., |mut row| {
                         row.col(|ui| {
-                            ui.label(name);
+                            let mut checked = item.is_checked(meal_planner);
+                            let mut text = RichText::new(&item.food);
+                            if checked {
+                                text = text.strikethrough().weak();
+                            }
+                            if ui.checkbox(&mut checked, text).changed() {
+                                item.set_checked(meal_planner, checked);
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(item.quantity.to_string());
+                            if let Some(in_pantry) = &item.in_pantry {
+                                ui.label(hs(&format!("+ {} in pantry", in_pantry)));
+                            }
+                            if let Some(packing) = item.packing_text() {
+                                ui.label(hs(&format!("buy {}", packing)));
+                            }
                         });
                         row.col(|ui| {
-                            ui.label(format!("{}g", weight));
+                            ui.label(item.weight().unwrap_or("—".to_string()));
+                        });
+                        row.col(|ui| {
+                            ui.horizontal(|ui| self.aisle_ui(ui, &item));
+                        });
+                        row.col(|ui| {
+                            if ui
+                                .button(ICON_PACKAGE_PLUS)
+                                .on_hover_text("Mark as bought and move to the pantry")
+                                .clicked()
+                            {
+                                mark_bought(meal_planner, &item);
+                            }
                         });
                     })
                 }
             });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::Ingredient, plan::PlanEntry};
+
+    #[test]
+    fn includes_only_the_chosen_days_and_meals() {
+        let meal_planner = MealPlanner::default();
+        let mut list = ShoppingList::default();
+        assert!(list.includes(&meal_planner, 5, 3));
+        list.days = Some((1, 2));
+        list.skipped_slots = vec![meal_planner.get_meal_slots()[3].clone()];
+        assert!(!list.includes(&meal_planner, 0, 0));
+        assert!(list.includes(&meal_planner, 1, 0));
+        assert!(list.includes(&meal_planner, 2, 2));
+        assert!(!list.includes(&meal_planner, 2, 3));
+        assert!(!list.includes(&meal_planner, 3, 0));
+    }
+
+    fn item(food: &str, quantity: Quantity, grams: f32) -> ShoppingItem {
+        ShoppingItem {
+            key: food.to_string(),
+            food: food.to_string(),
+            aisle: OTHER.to_string(),
+            quantity,
+            grams,
+            in_pantry: None,
+            packing: None,
+        }
+    }
+
+    fn pantry(food: &str, quantity: Quantity) -> Vec<PantryItem> {
+        vec![PantryItem::new(food, quantity, None)]
+    }
+
+    #[test]
+    fn pantry_covers_part_or_all_of_an_item() {
+        let names = FoodNames::default();
+        let mut partly = item("rice", Quantity::grams(500.), 500.);
+        partly.subtract_pantry(&pantry("Rice", Quantity::grams(200.)), &names);
+        assert_eq!(partly.quantity, Quantity::grams(300.));
+        assert_eq!(partly.in_pantry, Some(Quantity::grams(200.)));
+        assert_eq!(partly.grams, 300.);
+        assert!(!partly.is_covered());
+
+        let mut fully = item("green onion", Quantity::grams(500.), 500.);
+        let have = pantry("spring onions", Quantity::new(1., Unit::Kilogram));
+        fully.subtract_pantry(&have, &names);
+        assert_eq!(fully.in_pantry, Some(Quantity::grams(500.)));
+        assert_eq!(fully.grams, 0.);
+        assert!(fully.is_covered());
+    }
+
+    #[test]
+    fn pantry_converts_through_the_weight() {
+        let names = FoodNames::default();
+        let mut flour = item("flour", Quantity::new(2., Unit::Cup), 250.);
+        flour.subtract_pantry(&pantry("flour", Quantity::grams(125.)), &names);
+        assert_eq!(flour.quantity, Quantity::new(1., Unit::Cup));
+        assert_eq!(flour.grams, 125.);
+
+        // Without a weight, grams can't be told in pieces.
+        let eggs = Quantity::new(3., Unit::parse(""));
+        let mut unweighed = item("egg", eggs.clone(), 0.);
+        unweighed.subtract_pantry(&pantry("eggs", Quantity::grams(100.)), &names);
+        assert_eq!(unweighed.quantity, eggs);
+        assert_eq!(unweighed.in_pantry, None);
+
+        let mut other_food = item("egg", eggs.clone(), 150.);
+        other_food.subtract_pantry(&pantry("eggplant", Quantity::grams(100.)), &names);
+        assert_eq!(other_food.quantity, eggs);
+    }
+
+    /// A planner with a one-serving recipe of `(food, quantity, measure, grams)`
+    /// ingredients.
+    fn planner_with_recipe(ingredients: &[(&str, f32, &str, f32)]) -> (MealPlanner, Uuid) {
+        let mut meal_planner = MealPlanner::default();
+        let recipe = meal_planner.create_draft_recipe().unwrap();
+        recipe.title = "Salad".to_string();
+        let parsed = ingredients
+            .iter()
+            .map(|(food, quantity, measure, weight)| ParsedNutrient {
+                quantity: *quantity,
+                measure: Some(measure.to_string()),
+                foodMatch: None,
+                food: food.to_string(),
+                foodId: String::new(),
+                foodCategory: None,
+                weight: *weight,
+                retainedWeight: *weight,
+                nutrients: Default::default(),
+                measureURI: None,
+                status: String::new(),
+            })
+            .collect();
+        recipe.macros.ingredients = vec![Ingredient {
+            text: String::new(),
+            parsed: Some(parsed),
+        }];
+        let recipe_id = recipe.id;
+        meal_planner.delete_draft_recipe();
+        (meal_planner, recipe_id)
+    }
+
+    fn extra(name: &str, quantity: Quantity) -> ExtraItem {
+        ExtraItem {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            quantity,
+            aisle: OTHER.to_string(),
+        }
+    }
+
+    #[test]
+    fn extras_merge_with_the_same_food() {
+        let (mut meal_planner, recipe_id) =
+            planner_with_recipe(&[("tomato", 200., "gram", 200.), ("scallion", 2., "", 30.)]);
+        meal_planner.add_recipe_to_planner(0, 0, 0, PlanEntry::new(recipe_id));
+        meal_planner.add_shopping_extra(extra("Tomatoes", Quantity::new(0.3, Unit::Kilogram)));
+        meal_planner.add_shopping_extra(extra("spring onion", Quantity::new(1., Unit::parse(""))));
+        let cans = extra("tomato", Quantity::new(2., Unit::parse("can")));
+        meal_planner.add_shopping_extra(cans.clone());
+
+        let list = ShoppingList::default();
+        let items = list.shopping_list(meal_planner.get_daily_plan(), &meal_planner);
+        assert_eq!(items.len(), 3);
+        let find = |key: &str| items.iter().find(|item| item.key == key).unwrap();
+        let tomato = find("tomato");
+        assert_eq!(tomato.quantity, Quantity::grams(500.));
+        assert_eq!(tomato.grams, 500.);
+        let onion = find("green onion");
+        assert_eq!(onion.quantity, Quantity::new(3., Unit::parse("")));
+        assert_eq!(onion.grams, 45.);
+        let cans_item = find(&ExtraItem::key(&cans.id));
+        assert_eq!(cans_item.quantity, cans.quantity);
+
+        for item in &items {
+            item.set_checked(&mut meal_planner, true);
+        }
+        assert!(items.iter().all(|item| item.is_checked(&meal_planner)));
+    }
+
+    #[test]
+    fn changing_the_range_unchecks_only_changed_amounts() {
+        let (mut meal_planner, recipe_id) =
+            planner_with_recipe(&[("tomato", 200., "gram", 200.), ("scallion", 2., "", 30.)]);
+        meal_planner.add_recipe_to_planner(0, 0, 0, PlanEntry::new(recipe_id));
+        meal_planner.add_recipe_to_planner(1, 0, 0, PlanEntry::new(recipe_id));
+        meal_planner.add_shopping_extra(extra("tomatoes", Quantity::grams(100.)));
+        meal_planner.add_shopping_extra(extra("bread", Quantity::new(1., Unit::parse(""))));
+
+        let mut list = ShoppingList::default();
+        for item in list.shopping_list(meal_planner.get_daily_plan(), &meal_planner) {
+            item.set_checked(&mut meal_planner, true);
+        }
+        let checked = |list: &ShoppingList, meal_planner: &MealPlanner| {
+            let items = list.shopping_list(meal_planner.get_daily_plan(), meal_planner);
+            items
+                .iter()
+                .map(|item| (item.food.to_lowercase(), item.is_checked(meal_planner)))
+                .collect::<HashMap<String, bool>>()
+        };
+
+        // Skipping a meal with nothing planned leaves every amount as it was.
+        list.skipped_slots = vec![meal_planner.get_meal_slots()[3].clone()];
+        assert!(checked(&list, &meal_planner)
+            .values()
+            .all(|checked| *checked));
+
+        // Dropping a day needs less of the recipe's foods, merged extras included.
+        list.days = Some((0, 0));
+        let checked = checked(&list, &meal_planner);
+        assert!(!checked["tomato"]);
+        assert!(!checked["green onion"]);
+        assert!(checked["bread"]);
+    }
+}
@@ -0,0 +1,442 @@
+use egui::{Color32, ComboBox, RichText};
+use egui_extras::{Column, TableBuilder};
+use uuid::Uuid;
+
+use crate::{
+    meal_planner::MealPlanner,
+    models::{Macros, Recipe},
+    plan::{DayPlan, PlanEntry},
+    prices::{day_costs, format_cost, Cost, DAYS_PER_WEEK},
+    util::{hb, hs, DEFAULT_PADDING},
+};
+
+/// Nutrients shown in the summary: Edamam code, label and unit.
+const SUMMARY_NUTRIENTS: [(&str, &str, &str); 13] = [
+    ("ENERC_KCAL", "Calories", "kcal"),
+    ("PROCNT", "Protein", "g"),
+    ("CHOCDF", "Carbohydrates", "g"),
+    ("FAT", "Fat", "g"),
+    ("FIBTG", "Fiber", "g"),
+    ("SUGAR", "Sugars", "g"),
+    ("NA", "Sodium", "mg"),
+    ("CA", "Calcium", "mg"),
+    ("FE", "Iron", "mg"),
+    ("K", "Potassium", "mg"),
+    ("MG", "Magnesium", "mg"),
+    ("VITC", "Vitamin C", "mg"),
+    ("VITD", "Vitamin D", "µg"),
+];
+
+/// A day is highlighted when it is further than this from a target.
+const TARGET_TOLERANCE: f32 = 0.1;
+const TOP_CONTRIBUTORS: usize = 5;
+
+/// Amount of a nutrient in `servings` servings of a recipe. Calories come from the
+/// recipe total, which is also what the rest of the app displays.
+pub fn nutrient_amount(recipe: &Recipe, code: &str, servings: f32) -> f32 {
+    let per_serving = if code == "ENERC_KCAL" {
+        recipe.macros.calories as f32 / recipe.servings as f32
+    } else {
+        recipe.nutrient_per_serving(code)
+    };
+    per_serving * servings
+}
+
+/// Servings of an entry eaten by `member`, or by the whole household without one.
+fn entry_servings(
+    meal_planner: &MealPlanner,
+    entry: &PlanEntry,
+    recipe: &Recipe,
+    member: Option<&Uuid>,
+) -> f32 {
+    match member {
+        Some(member) => meal_planner.member_servings(entry, member, recipe),
+        None => meal_planner.entry_servings(entry, recipe),
+    }
+}
+
+fn day_amount(meal_planner: &MealPlanner, day: &DayPlan, member: Option<&Uuid>, code: &str) -> f32 {
+    day.entries()
+        .filter_map(|entry| {
+            let recipe = meal_planner.get_recipe_by_id(&entry.recipe_id)?;
+            let servings = entry_servings(meal_planner, entry, recipe, member);
+            Some(nutrient_amount(recipe, code, servings))
+        })
+        .sum()
+}
+
+/// Calories and macros of one planned day, eaten by `member` or by the whole household.
+pub fn day_macros(meal_planner: &MealPlanner, day: &DayPlan, member: Option<&Uuid>) -> Macros {
+    Macros {
+        calories: day_amount(meal_planner, day, member, "ENERC_KCAL"),
+        protein: day_amount(meal_planner, day, member, "PROCNT"),
+        carbs: day_amount(meal_planner, day, member, "CHOCDF"),
+        fat: day_amount(meal_planner, day, member, "FAT"),
+    }
+}
+
+/// Colours a value red when it is over the target and blue when it is under,
+/// outside of [`TARGET_TOLERANCE`].
+pub fn target_text(value: f32, target: f32) -> RichText {
+    let text = hs(&format!("{:.0}", value));
+    if target <= 0. {
+        return text;
+    }
+    let ratio = value / target;
+    if ratio > 1. + TARGET_TOLERANCE {
+        text.color(Color32::from_rgb(200, 60, 50)).strong()
+    } else if ratio < 1. - TARGET_TOLERANCE {
+        text.color(Color32::from_rgb(40, 110, 190)).strong()
+    } else {
+        text
+    }
+}
+
+fn cost_row(body: &mut egui_extras::TableBody, label: RichText, cost: &Cost, budget: Option<f32>) {
+    body.row(20., |mut row| {
+        row.col(|ui| {
+            ui.label(label);
+        });
+        row.col(|ui| {
+            let over_budget = budget.is_some_and(|budget| cost.amount > budget);
+            let text = hs(&cost.label());
+            let response = if over_budget {
+                ui.label(text.color(Color32::from_rgb(200, 60, 50)).strong())
+            } else {
+                ui.label(text)
+            };
+            if !cost.is_complete() {
+                response.on_hover_text(cost.unpriced_text());
+            }
+        });
+    });
+}
+
+/// Weekly totals and averages of the current plan.
+#[derive(Debug, Default)]
+pub struct PlanSummary {
+    contributors_nutrient: usize,
+    /// Household member whose share is summarized, the whole household if `None`.
+    member: Option<Uuid>,
+}
+
+impl PlanSummary {
+    /// Picks whose share of the plan is summarized. Returns the member, `None` for
+    /// the whole household, with the daily targets to compare against.
+    fn member_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        meal_planner: &MealPlanner,
+    ) -> (Option<Uuid>, Macros) {
+        let members = meal_planner.get_members();
+        if members.is_empty() {
+            return (None, meal_planner.targets);
+        }
+        let selected = members.iter().find(|member| Some(member.id) == self.member);
+        ui.horizontal(|ui| {
+            ui.label(hb("Summary for"));
+            ComboBox::from_id_salt("summary_member")
+                .selected_text(selected.map_or("Household", |member| member.name.as_str()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.member, None, "Household");
+                    for member in members {
+                        ui.selectable_value(&mut self.member, Some(member.id), &member.name);
+                    }
+                });
+        });
+        match members.iter().find(|member| Some(member.id) == self.member) {
+            Some(member) => (Some(member.id), member.targets),
+            None => (
+                None,
+                members
+                    .iter()
+                    .fold(Macros::default(), |total, member| total + member.targets),
+            ),
+        }
+    }
+
+    /// What each day and week of the plan costs, against the weekly budget.
+    fn cost_ui(ui: &mut egui::Ui, meal_planner: &MealPlanner) {
+        ui.label(hb("Cost"));
+        if meal_planner.get_prices().is_empty() {
+            ui.label(hs("Add ingredient prices to see what the plan costs."));
+            return;
+        }
+        let plan = meal_planner.get_daily_plan();
+        let costs = day_costs(plan, meal_planner);
+        let budget = meal_planner.weekly_budget;
+        let weeks: Vec<Cost> = costs
+            .chunks(DAYS_PER_WEEK)
+            .map(|days| {
+                let mut week = Cost::default();
+                for day in days {
+                    week.add(day.clone());
+                }
+                week
+            })
+            .collect();
+
+        let mut unpriced = Cost::default();
+        for week in &weeks {
+            unpriced.add(week.clone());
+        }
+        if !unpriced.is_complete() {
+            ui.label(hs(&format!(
+                "Costs marked + leave out ingredients without a price: {}.",
+                unpriced
+                    .unpriced
+                    .into_iter()
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )));
+        }
+        if let Some(budget) = budget {
+            for (week_idx, week) in weeks.iter().enumerate() {
+                if week.amount > budget {
+                    ui.label(
+                        RichText::new(format!(
+                            "Week {} is {} over the budget of {}.",
+                            week_idx + 1,
+                            format_cost(week.amount - budget),
+                            format_cost(budget)
+                        ))
+                        .color(Color32::from_rgb(200, 60, 50)),
+                    );
+                }
+            }
+        }
+
+        ui.push_id("summary_costs", |ui| {
+            TableBuilder::new(ui)
+                .striped(true)
+                .vscroll(false)
+                .column(Column::remainder())
+                .column(Column::auto().at_least(80.))
+                .header(24., |mut header| {
+                    header.col(|ui| {
+                        ui.strong("Day");
+                    });
+                    header.col(|ui| {
+                        ui.strong("Cost");
+                    });
+                })
+                .body(|mut body| {
+                    for (week_idx, week) in weeks.iter().enumerate() {
+                        let first_day = week_idx * DAYS_PER_WEEK;
+                        for (day_idx, cost) in
+                            costs.iter().enumerate().skip(first_day).take(DAYS_PER_WEEK)
+                        {
+                            let label = hs(&format!("Day {}", day_idx + 1));
+                            cost_row(&mut body, label, cost, None);
+                        }
+                        let label = hb(&format!("Week {}", week_idx + 1));
+                        cost_row(&mut body, label, week, budget);
+                    }
+                });
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &MealPlanner) {
+        let plan = meal_planner.get_daily_plan();
+        let planned_days = plan.iter().filter(|day| !day.is_empty()).count();
+        if planned_days == 0 {
+            ui.label("Add some meals to the planner to see a summary.");
+            return;
+        }
+        let (member, targets) = self.member_ui(ui, meal_planner);
+        let member = member.as_ref();
+
+        ui.label(hb("Totals"));
+        ui.label(hs(&format!(
+            "Averages are taken over the {} of {} days that have meals.",
+            planned_days,
+            plan.len()
+        )));
+        ui.push_id("summary_totals", |ui| {
+            TableBuilder::new(ui)
+                .striped(true)
+                .vscroll(false)
+                .column(Column::remainder())
+                .column(Column::auto().at_least(80.))
+                .column(Column::auto().at_least(80.))
+                .header(24., |mut header| {
+                    header.col(|ui| {
+                        ui.strong("Nutrient");
+                    });
+                    header.col(|ui| {
+                        ui.strong("Total");
+                    });
+                    header.col(|ui| {
+                        ui.strong("Per day");
+                    });
+                })
+                .body(|mut body| {
+                    for (code, label, unit) in SUMMARY_NUTRIENTS {
+                        let total: f32 = plan
+                            .iter()
+                            .map(|day| day_amount(meal_planner, day, member, code))
+                            .sum();
+                        body.row(20., |mut row| {
+                            row.col(|ui| {
+                                ui.label(hs(label));
+                            });
+                            row.col(|ui| {
+                                ui.label(hs(&format!("{:.0}{}", total, unit)));
+                            });
+                            row.col(|ui| {
+                                ui.label(hs(&format!(
+                                    "{:.0}{}",
+                                    total / planned_days as f32,
+                                    unit
+                                )));
+                            });
+                        });
+                    }
+                });
+        });
+
+        ui.add_space(DEFAULT_PADDING);
+        ui.separator();
+        ui.label(hb("Days against targets"));
+        ui.label(hs(&format!(
+            "Values more than {:.0}% over a target are red, under a target blue.",
+            TARGET_TOLERANCE * 100.
+        )));
+        ui.push_id("summary_days", |ui| {
+            TableBuilder::new(ui)
+                .striped(true)
+                .vscroll(false)
+                .column(Column::remainder())
+                .columns(Column::auto().at_least(60.), 4)
+                .header(24., |mut header| {
+                    for title in ["Day", "kcal", "Protein", "Carbs", "Fat"] {
+                        header.col(|ui| {
+                            ui.strong(title);
+                        });
+                    }
+                })
+                .body(|mut body| {
+                    body.row(20., |mut row| {
+                        row.col(|ui| {
+                            ui.label(hs("Target"));
+                        });
+                        for value in [
+                            targets.calories,
+                            targets.protein,
+                            targets.carbs,
+                            targets.fat,
+                        ] {
+                            row.col(|ui| {
+                                ui.label(hs(&format!("{:.0}", value)));
+                            });
+                        }
+                    });
+                    for (day_idx, day) in plan.iter().enumerate() {
+                        let macros = day_macros(meal_planner, day, member);
+                        body.row(20., |mut row| {
+                            row.col(|ui| {
+                                ui.label(hs(&format!("Day {}", day_idx + 1)));
+                            });
+                            let values = [
+                                (macros.calories, targets.calories),
+                                (macros.protein, targets.protein),
+                                (macros.carbs, targets.carbs),
+                                (macros.fat, targets.fat),
+                            ];
+                            for (value, target) in values {
+                                row.col(|ui| {
+                                    if day.is_empty() {
+                                        ui.label(hs("-"));
+                                    } else {
+                                        ui.label(target_text(value, target));
+                                    }
+                                });
+                            }
+                        });
+                    }
+                });
+        });
+
+        ui.add_space(DEFAULT_PADDING);
+        ui.separator();
+        Self::cost_ui(ui, meal_planner);
+
+        ui.add_space(DEFAULT_PADDING);
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(hb("Top contributors to"));
+            ComboBox::from_id_salt("summary_contributors")
+                .selected_text(SUMMARY_NUTRIENTS[self.contributors_nutrient].1)
+                .show_ui(ui, |ui| {
+                    for (idx, (_, label, _)) in SUMMARY_NUTRIENTS.iter().enumerate() {
+                        ui.selectable_value(&mut self.contributors_nutrient, idx, *label);
+                    }
+                });
+        });
+
+        let (code, _, unit) = SUMMARY_NUTRIENTS[self.contributors_nutrient];
+        let mut contributions: Vec<(Uuid, f32)> = vec![];
+        for entry in plan.iter().flat_map(|day| day.entries()) {
+            let Some(recipe) = meal_planner.get_recipe_by_id(&entry.recipe_id) else {
+                continue;
+            };
+            let servings = entry_servings(meal_planner, entry, recipe, member);
+            let amount = nutrient_amount(recipe, code, servings);
+            match contributions.iter_mut().find(|(id, _)| *id == recipe.id) {
+                Some((_, total)) => *total += amount,
+                None => contributions.push((recipe.id, amount)),
+            }
+        }
+        let total: f32 = contributions.iter().map(|(_, amount)| amount).sum();
+        contributions.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        for (recipe_id, amount) in contributions.iter().take(TOP_CONTRIBUTORS) {
+            let title = meal_planner
+                .get_recipe_by_id(recipe_id)
+                .map(|recipe| recipe.to_string())
+                .unwrap_or_default();
+            let share = if total > 0. {
+                amount / total * 100.
+            } else {
+                0.
+            };
+            ui.horizontal(|ui| {
+                ui.label(hs(&title));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(hs(&format!("{:.0}{} ({:.0}%)", amount, unit, share)));
+                });
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::Portion;
+
+    #[test]
+    fn day_macros_count_every_member() {
+        let mut meal_planner = MealPlanner::default();
+        let recipe = meal_planner.create_draft_recipe().unwrap();
+        recipe.servings = 4;
+        recipe.macros.calories = 2000;
+        let recipe_id = recipe.id;
+        let mut entry = PlanEntry::new(recipe_id);
+        entry.portion = Portion::Servings(2.);
+        meal_planner.add_recipe_to_planner(0, 0, 0, entry);
+        let day = meal_planner.get_daily_plan()[0].clone();
+        assert_eq!(day_macros(&meal_planner, &day, None).calories, 1000.);
+
+        meal_planner.add_member("Ann");
+        meal_planner.add_member("Bo");
+        let ann = meal_planner.get_members()[0].id;
+        assert_eq!(day_macros(&meal_planner, &day, None).calories, 2000.);
+        assert_eq!(day_macros(&meal_planner, &day, Some(&ann)).calories, 1000.);
+
+        meal_planner.set_member_share((0, 0, 0), ann, Some(Portion::Servings(1.)));
+        let day = meal_planner.get_daily_plan()[0].clone();
+        assert_eq!(day_macros(&meal_planner, &day, None).calories, 1500.);
+        assert_eq!(day_macros(&meal_planner, &day, Some(&ann)).calories, 500.);
+    }
+}
@@ -0,0 +1,130 @@
+use egui::{Button, ComboBox, Grid};
+
+use crate::{
+    meal_planner::MealPlanner,
+    typography::icons::{ICON_BOOKMARK_PLUS, ICON_CHECK, ICON_TRASH_2},
+    util::{day_range_ui, hb, hs, DEFAULT_PADDING},
+};
+
+/// Saves days of the plan as named templates and applies them again.
+#[derive(Debug, Default)]
+pub struct TemplateLibrary {
+    name: String,
+    save_from: usize,
+    save_to: usize,
+    selected: usize,
+    apply_from: usize,
+    apply_to: usize,
+    replace: bool,
+}
+
+impl TemplateLibrary {
+    pub fn ui(&mut self, ui: &mut egui::Ui, meal_planner: &mut MealPlanner) {
+        let plan_length = meal_planner.plan_length();
+
+        ui.group(|ui| {
+            ui.label(hb("Save as template"));
+            Grid::new("template_save").num_columns(2).show(ui, |ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(&mut self.name);
+                ui.end_row();
+                ui.label("Days");
+                ui.horizontal(|ui| {
+                    day_range_ui(ui, &mut self.save_from, &mut self.save_to, plan_length);
+                    if ui.button("Whole plan").clicked() {
+                        self.save_from = 0;
+                        self.save_to = plan_length - 1;
+                    }
+                });
+                ui.end_row();
+            });
+            let name = self.name.trim();
+            let exists = meal_planner.get_templates().iter().any(|t| t.name == name);
+            let save_btn = Button::new(format!("{} Save template", ICON_BOOKMARK_PLUS));
+            let response = ui.add_enabled(!name.is_empty(), save_btn);
+            let response = if exists {
+                response.on_hover_text("Overwrites the template with the same name")
+            } else {
+                response
+            };
+            if response.clicked() {
+                meal_planner.save_template(name, self.save_from, self.save_to);
+                self.name.clear();
+            }
+        });
+
+        ui.add_space(DEFAULT_PADDING);
+        let templates = meal_planner.get_templates();
+        if templates.is_empty() {
+            ui.label(hs(
+                "No templates yet. Save a day or the whole plan to reuse it later.",
+            ));
+            return;
+        }
+        self.selected = self.selected.min(templates.len() - 1);
+
+        let mut remove = None;
+        ui.group(|ui| {
+            ui.label(hb("Templates"));
+            Grid::new("template_library")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (idx, template) in templates.iter().enumerate() {
+                        ui.radio_value(&mut self.selected, idx, &template.name);
+                        let days = template.days.len();
+                        let meals: usize = template.days.iter().map(|d| d.entries().count()).sum();
+                        ui.label(hs(&format!(
+                            "{} day{} · {} meals",
+                            days,
+                            if days == 1 { "" } else { "s" },
+                            meals
+                        )));
+                        if ui
+                            .button(ICON_TRASH_2)
+                            .on_hover_text("Delete template")
+                            .clicked()
+                        {
+                            remove = Some(idx);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+        ui.group(|ui| {
+            ui.label(hb("Apply template"));
+            ui.horizontal(|ui| {
+                day_range_ui(ui, &mut self.apply_from, &mut self.apply_to, plan_length);
+            });
+            ComboBox::from_id_salt("template_apply_mode")
+                .selected_text(if self.replace {
+                    "Replace planned meals"
+                } else {
+                    "Add to planned meals"
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.replace, false, "Add to planned meals");
+                    ui.selectable_value(&mut self.replace, true, "Replace planned meals");
+                });
+            ui.label(hs(
+                "A template shorter than the range is repeated until the range is filled.",
+            ));
+            if ui
+                .button(format!("{} Apply to planner", ICON_CHECK))
+                .clicked()
+            {
+                meal_planner.apply_template(
+                    self.selected,
+                    self.apply_from,
+                    self.apply_to,
+                    self.replace,
+                );
+            }
+        });
+
+        if let Some(idx) = remove {
+            meal_planner.remove_template(idx);
+        }
+    }
+}
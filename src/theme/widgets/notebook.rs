@@ -35,7 +35,7 @@ impl Notebook {
         }
 
         // 4) Place a text editor *over* the same rect with a transparent background
-        ui.allocate_ui_at_rect(rect, |ui| {
+        ui.allocate_new_ui(egui::UiBuilder::new().max_rect(rect), |ui| {
             // We want the text editor to fill the entire rect:
             let size = ui.available_size(); // same as `rect.size()`
 
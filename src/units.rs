@@ -0,0 +1,327 @@
+use serde::{Deserialize, Serialize};
+
+use crate::food_names::normalize;
+
+/// What a unit measures. Amounts can only be added up within the same dimension,
+/// pieces only when they are the same kind of piece.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Dimension {
+    Weight,
+    Volume,
+    Pieces(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Unit {
+    Gram,
+    Kilogram,
+    Ounce,
+    Pound,
+    Millilitre,
+    Litre,
+    Teaspoon,
+    Tablespoon,
+    FluidOunce,
+    Cup,
+    Pint,
+    Quart,
+    Gallon,
+    /// Counted items. Empty for whole items ("12 eggs"), otherwise what is counted
+    /// ("3 cloves").
+    Piece(String),
+}
+
+/// Units a quantity is moved up to when it gets large, smallest first.
+static WEIGHT_METRIC: [Unit; 2] = [Unit::Gram, Unit::Kilogram];
+static WEIGHT_IMPERIAL: [Unit; 2] = [Unit::Ounce, Unit::Pound];
+static VOLUME_METRIC: [Unit; 2] = [Unit::Millilitre, Unit::Litre];
+static VOLUME_US: [Unit; 3] = [Unit::Teaspoon, Unit::Tablespoon, Unit::Cup];
+
+impl Unit {
+    /// Reads an Edamam measure ("tablespoon", "<unit>") or a unit typed by the user
+    /// ("tbsp", "kg"). Anything else is counted as pieces of that name.
+    pub fn parse(measure: &str) -> Unit {
+        let measure = measure.trim().to_lowercase();
+        match measure.as_str() {
+            "g" | "gr" | "gram" | "grams" => Unit::Gram,
+            "kg" | "kilo" | "kilogram" | "kilograms" => Unit::Kilogram,
+            "oz" | "ounce" | "ounces" => Unit::Ounce,
+            "lb" | "lbs" | "pound" | "pounds" => Unit::Pound,
+            "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => Unit::Millilitre,
+            "l" | "liter" | "liters" | "litre" | "litres" => Unit::Litre,
+            "tsp" | "teaspoon" | "teaspoons" => Unit::Teaspoon,
+            "tbsp" | "tablespoon" | "tablespoons" => Unit::Tablespoon,
+            "fl oz" | "fluid ounce" | "fluid ounces" => Unit::FluidOunce,
+            "cup" | "cups" => Unit::Cup,
+            "pint" | "pints" => Unit::Pint,
+            "quart" | "quarts" => Unit::Quart,
+            "gallon" | "gallons" => Unit::Gallon,
+            "" | "<unit>" | "unit" | "units" | "whole" | "piece" | "pieces" | "pc" | "pcs"
+            | "large" | "medium" | "small" => Unit::Piece(String::new()),
+            _ => Unit::Piece(normalize(&measure)),
+        }
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Unit::Gram | Unit::Kilogram | Unit::Ounce | Unit::Pound => Dimension::Weight,
+            Unit::Piece(name) => Dimension::Pieces(name.clone()),
+            _ => Dimension::Volume,
+        }
+    }
+
+    /// Size of the unit in grams, millilitres or pieces.
+    fn base_factor(&self) -> f32 {
+        match self {
+            Unit::Gram | Unit::Millilitre | Unit::Piece(_) => 1.,
+            Unit::Kilogram | Unit::Litre => 1000.,
+            Unit::Ounce => 28.3495,
+            Unit::Pound => 453.592,
+            Unit::Teaspoon => 4.92892,
+            Unit::Tablespoon => 14.7868,
+            Unit::FluidOunce => 29.5735,
+            Unit::Cup => 236.588,
+            Unit::Pint => 473.176,
+            Unit::Quart => 946.353,
+            Unit::Gallon => 3785.41,
+        }
+    }
+
+    /// The unit's short name, plural where it reads better for `amount`.
+    pub fn symbol(&self, amount: f32) -> String {
+        let plural = amount > 1.;
+        match self {
+            Unit::Gram => "g".to_string(),
+            Unit::Kilogram => "kg".to_string(),
+            Unit::Ounce => "oz".to_string(),
+            Unit::Pound => "lb".to_string(),
+            Unit::Millilitre => "ml".to_string(),
+            Unit::Litre => "l".to_string(),
+            Unit::Teaspoon => "tsp".to_string(),
+            Unit::Tablespoon => "tbsp".to_string(),
+            Unit::FluidOunce => "fl oz".to_string(),
+            Unit::Cup if plural => "cups".to_string(),
+            Unit::Cup => "cup".to_string(),
+            Unit::Pint if plural => "pints".to_string(),
+            Unit::Pint => "pint".to_string(),
+            Unit::Quart if plural => "quarts".to_string(),
+            Unit::Quart => "quart".to_string(),
+            Unit::Gallon if plural => "gallons".to_string(),
+            Unit::Gallon => "gallon".to_string(),
+            Unit::Piece(name) if plural && !name.is_empty() => pluralize(name),
+            Unit::Piece(name) => name.clone(),
+        }
+    }
+}
+
+/// Plural of a singular piece name, undoing [`normalize`].
+fn pluralize(name: &str) -> String {
+    if ["ch", "sh", "ss", "x"]
+        .iter()
+        .any(|end| name.ends_with(end))
+    {
+        format!("{}es", name)
+    } else if let Some(stem) = name
+        .strip_suffix('y')
+        .filter(|stem| !stem.ends_with(['a', 'e', 'i', 'o', 'u']))
+    {
+        format!("{}ies", stem)
+    } else {
+        format!("{}s", name)
+    }
+}
+
+/// Formats an amount with at most two decimals, without trailing zeros.
+pub fn format_amount(amount: f32) -> String {
+    let text = format!("{:.2}", amount);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Quantity {
+    pub amount: f32,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    pub fn new(amount: f32, unit: Unit) -> Self {
+        Self { amount, unit }
+    }
+
+    pub fn grams(amount: f32) -> Self {
+        Self::new(amount, Unit::Gram)
+    }
+
+    /// This quantity in `unit`, if both measure the same dimension.
+    pub fn convert(&self, unit: &Unit) -> Option<Quantity> {
+        (self.unit.dimension() == unit.dimension()).then(|| {
+            Quantity::new(
+                self.amount * self.unit.base_factor() / unit.base_factor(),
+                unit.clone(),
+            )
+        })
+    }
+
+    /// The same quantity in the largest unit of its system it is at least one of,
+    /// so 1500 g reads as 1.5 kg and 6 tsp as 2 tbsp.
+    pub fn simplified(&self) -> Quantity {
+        let ladder: &[Unit] = [
+            &WEIGHT_METRIC[..],
+            &WEIGHT_IMPERIAL,
+            &VOLUME_METRIC,
+            &VOLUME_US,
+        ]
+        .into_iter()
+        .find(|ladder| ladder.contains(&self.unit))
+        .unwrap_or_default();
+        ladder
+            .iter()
+            .rev()
+            .filter_map(|unit| self.convert(unit))
+            .find(|quantity| quantity.amount >= 1.)
+            .unwrap_or_else(|| self.clone())
+    }
+}
+
+impl std::fmt::Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = self.unit.symbol(self.amount);
+        match self.unit {
+            Unit::Piece(_) if symbol.is_empty() => write!(f, "{}", format_amount(self.amount)),
+            Unit::Piece(_) => write!(f, "{} {}", format_amount(self.amount), symbol),
+            _ if symbol.len() <= 2 => write!(f, "{}{}", format_amount(self.amount), symbol),
+            _ => write!(f, "{} {}", format_amount(self.amount), symbol),
+        }
+    }
+}
+
+/// Adds up amounts of one food given in different units, using the gram weights
+/// that come with each of them to go between weights, volumes and pieces.
+#[derive(Clone, Debug, Default)]
+pub struct QuantitySum {
+    /// Each part as its quantity and weight in grams.
+    parts: Vec<(Quantity, f32)>,
+}
+
+impl QuantitySum {
+    pub fn add(&mut self, quantity: Quantity, grams: f32) {
+        self.parts.push((quantity, grams));
+    }
+
+    pub fn grams(&self) -> f32 {
+        self.parts.iter().map(|(_, grams)| grams).sum()
+    }
+
+    /// The unit most of the weight is given in.
+    fn natural_unit(&self) -> Option<Unit> {
+        let mut by_unit: Vec<(Unit, f32)> = vec![];
+        for (quantity, grams) in &self.parts {
+            match by_unit.iter_mut().find(|(unit, _)| *unit == quantity.unit) {
+                Some((_, total)) => *total += grams,
+                None => by_unit.push((quantity.unit.clone(), *grams)),
+            }
+        }
+        by_unit
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(unit, _)| unit)
+    }
+
+    /// The total in the unit most of it is given in. Parts in other dimensions are
+    /// converted through their weight, using how much the natural unit weighs.
+    pub fn total(&self) -> Quantity {
+        let Some(unit) = self.natural_unit() else {
+            return Quantity::grams(0.);
+        };
+        if unit.dimension() == Dimension::Weight {
+            return Quantity::grams(self.grams()).convert(&unit).unwrap();
+        }
+
+        let (same, same_grams) = self
+            .parts
+            .iter()
+            .filter_map(|(quantity, grams)| Some((quantity.convert(&unit)?.amount, *grams)))
+            .fold((0., 0.), |(amount, total), (a, g)| (amount + a, total + g));
+        let grams_per_unit = same_grams / same;
+        if !grams_per_unit.is_normal() {
+            return Quantity::grams(self.grams());
+        }
+        let other_grams = self.grams() - same_grams;
+        Quantity::new(same + other_grams / grams_per_unit, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_known_units() {
+        assert_eq!(Unit::parse(" Tablespoons "), Unit::Tablespoon);
+        assert_eq!(Unit::parse("kg"), Unit::Kilogram);
+        assert_eq!(Unit::parse("fl oz"), Unit::FluidOunce);
+        assert_eq!(Unit::parse("<unit>"), Unit::Piece(String::new()));
+        assert_eq!(Unit::parse("large"), Unit::Piece(String::new()));
+    }
+
+    #[test]
+    fn parse_pieces_as_singular() {
+        assert_eq!(Unit::parse("cloves"), Unit::Piece("clove".to_string()));
+        assert_eq!(Unit::parse("Pinches"), Unit::Piece("pinch".to_string()));
+        assert_eq!(Unit::parse("berries"), Unit::Piece("berry".to_string()));
+        assert_eq!(Unit::parse("glass"), Unit::Piece("glass".to_string()));
+    }
+
+    #[test]
+    fn piece_symbols_are_plural_above_one() {
+        for name in ["clove", "pinch", "berry", "glass", "box", "tray"] {
+            let unit = Unit::parse(name);
+            assert_eq!(unit.symbol(1.), name);
+            assert_eq!(Unit::parse(&unit.symbol(2.)), unit);
+        }
+        assert_eq!(Unit::parse("pinch").symbol(2.), "pinches");
+    }
+
+    #[test]
+    fn quantities_display_and_simplify() {
+        assert_eq!(Quantity::grams(1500.).simplified().to_string(), "1.5kg");
+        assert_eq!(
+            Quantity::new(6., Unit::Teaspoon).simplified().to_string(),
+            "2 tbsp"
+        );
+        assert_eq!(
+            Quantity::new(3., Unit::Piece(String::new())).to_string(),
+            "3"
+        );
+        assert_eq!(Quantity::new(0.5, Unit::Cup).to_string(), "0.5 cup");
+    }
+
+    #[test]
+    fn total_in_the_unit_most_weight_is_given_in() {
+        let mut sum = QuantitySum::default();
+        sum.add(Quantity::new(2., Unit::Cup), 400.);
+        sum.add(Quantity::new(100., Unit::Gram), 100.);
+        let total = sum.total();
+        assert_eq!(total.unit, Unit::Cup);
+        assert!((total.amount - 2.5).abs() < 1e-4);
+        assert_eq!(sum.grams(), 500.);
+    }
+
+    #[test]
+    fn total_of_weights_and_empty_sums() {
+        let mut sum = QuantitySum::default();
+        assert_eq!(sum.total(), Quantity::grams(0.));
+        sum.add(Quantity::new(1., Unit::Pound), 453.592);
+        sum.add(Quantity::new(4., Unit::Ounce), 113.398);
+        let total = sum.total();
+        assert_eq!(total.unit, Unit::Pound);
+        assert!((total.amount - 1.25).abs() < 1e-4);
+    }
+
+    #[test]
+    fn total_without_weights_falls_back_to_grams() {
+        let mut sum = QuantitySum::default();
+        sum.add(Quantity::new(2., Unit::Piece(String::new())), 0.);
+        assert_eq!(sum.total(), Quantity::grams(0.));
+    }
+}
@@ -1,4 +1,6 @@
-use egui::RichText;
+use std::sync::{Arc, Mutex};
+
+use egui::{DragValue, RichText};
 
 use crate::{helvetica_body, helvetica_heading, helvetica_small, smallish};
 
@@ -23,3 +25,86 @@ pub fn hs(str: &str) -> RichText {
 pub fn ls(str: &str) -> RichText {
     RichText::new(str).text_style(smallish())
 }
+
+/// Lets the user pick a `from..=to` range of days, shown 1-based.
+pub fn day_range_ui(ui: &mut egui::Ui, from: &mut usize, to: &mut usize, plan_length: usize) {
+    let last = plan_length.saturating_sub(1);
+    *from = (*from).min(last);
+    *to = (*to).clamp(*from, last);
+    let mut first_day = *from + 1;
+    let mut last_day = *to + 1;
+    ui.label("Day");
+    ui.add(DragValue::new(&mut first_day).range(1..=plan_length));
+    ui.label("to");
+    ui.add(DragValue::new(&mut last_day).range(first_day..=plan_length));
+    *from = first_day - 1;
+    *to = last_day.max(first_day) - 1;
+}
+
+/// Lets the user save `content` to a file, suggesting `file_name`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(file_name: &str, content: Vec<u8>) {
+    let dialog = rfd::AsyncFileDialog::new()
+        .set_file_name(file_name)
+        .save_file();
+    std::thread::spawn(move || {
+        futures::executor::block_on(async move {
+            if let Some(file) = dialog.await {
+                if let Err(err) = file.write(&content).await {
+                    log::error!("Saving {:?} failed: {}", file.file_name(), err);
+                }
+            }
+        })
+    });
+}
+
+/// Lets the user pick a file of type `filter_name`, read into `content` once picked.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_file(filter_name: &str, extensions: &[&str], content: Arc<Mutex<Option<Vec<u8>>>>) {
+    let dialog = rfd::AsyncFileDialog::new()
+        .add_filter(filter_name, extensions)
+        .pick_file();
+    std::thread::spawn(move || {
+        futures::executor::block_on(async move {
+            if let Some(file) = dialog.await {
+                *content.lock().unwrap() = Some(file.read().await);
+            }
+        })
+    });
+}
+
+/// Lets the user pick a file of type `filter_name`, read into `content` once picked.
+#[cfg(target_arch = "wasm32")]
+pub fn open_file(filter_name: &str, extensions: &[&str], content: Arc<Mutex<Option<Vec<u8>>>>) {
+    let dialog = rfd::AsyncFileDialog::new()
+        .add_filter(filter_name, extensions)
+        .pick_file();
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Some(file) = dialog.await {
+            *content.lock().unwrap() = Some(file.read().await);
+        }
+    });
+}
+
+/// Downloads `content` as a file named `file_name`.
+#[cfg(target_arch = "wasm32")]
+pub fn save_file(file_name: &str, content: Vec<u8>) {
+    use base64::prelude::*;
+    use web_sys::wasm_bindgen::JsCast;
+
+    let win = web_sys::window().unwrap();
+    let doc = win.document().unwrap();
+
+    let link = doc.create_element("a").unwrap();
+    let _ = link.set_attribute(
+        "href",
+        &format!(
+            "data:application/octet-stream;base64,{}",
+            BASE64_STANDARD.encode(content)
+        ),
+    );
+    let _ = link.set_attribute("download", file_name);
+    let link: web_sys::HtmlAnchorElement =
+        web_sys::HtmlAnchorElement::unchecked_from_js(link.into());
+    link.click();
+}